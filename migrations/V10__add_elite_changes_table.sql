@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS elite_changes (
+    id                SERIAL PRIMARY KEY,
+    elite_id          INTEGER NOT NULL REFERENCES elites (id),
+    actor_discord_id  TEXT NOT NULL,
+    changes           JSONB NOT NULL,
+    timestamp         TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS elite_changes_elite_id_idx ON elite_changes (elite_id, timestamp);
+CREATE INDEX IF NOT EXISTS elite_changes_timestamp_idx ON elite_changes (timestamp);
+
+ALTER TABLE IF EXISTS elite_changes OWNER TO postgres;
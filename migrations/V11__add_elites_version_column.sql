@@ -0,0 +1,26 @@
+-- Row version used for optimistic concurrency on elite updates
+ALTER TABLE elites ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
+
+-- The view has to be recreated since `e.*` is expanded when the view is created
+DROP VIEW IF EXISTS elites_with_ign;
+
+CREATE VIEW elites_with_ign AS
+SELECT
+    e.*,
+    nh.ign,
+    (tu.uuid IS NOT NULL) AS being_tracked
+FROM
+    elites e
+LEFT JOIN LATERAL (
+    SELECT ign
+    FROM name_history
+    WHERE uuid = e.minecraft_uuid
+    ORDER BY timestamp DESC
+    LIMIT 1
+) nh ON true
+LEFT JOIN
+    tracked_uuids tu
+    ON e.minecraft_uuid = tu.uuid
+ORDER BY e.id;
+
+ALTER VIEW IF EXISTS elites_with_ign OWNER TO postgres;
@@ -0,0 +1,8 @@
+-- Per-user tokens authenticating calendar feed subscriptions, only the sha256 hash of a token is stored
+CREATE TABLE IF NOT EXISTS calendar_tokens (
+    discord_user_id  TEXT PRIMARY KEY,
+    token_hash       TEXT NOT NULL UNIQUE,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+ALTER TABLE IF EXISTS calendar_tokens OWNER TO postgres;
@@ -0,0 +1,10 @@
+-- Game servers pulling the whitelist and permission exports, authenticated by the sha256 hash of their api token
+CREATE TABLE IF NOT EXISTS minecraft_servers (
+    id            SERIAL PRIMARY KEY,
+    name          TEXT NOT NULL UNIQUE,
+    token_hash    TEXT NOT NULL UNIQUE,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_used_at  TIMESTAMPTZ
+);
+
+ALTER TABLE IF EXISTS minecraft_servers OWNER TO postgres;
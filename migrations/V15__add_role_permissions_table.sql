@@ -0,0 +1,10 @@
+-- Permission sets granted to members holding a discord role, in addition to the elite staff role which has every permission
+CREATE TABLE IF NOT EXISTS role_permissions (
+    discord_role_id  TEXT PRIMARY KEY,
+    name             TEXT NOT NULL,
+    permissions      TEXT[] NOT NULL,
+    updated_by       TEXT NOT NULL,
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+ALTER TABLE IF EXISTS role_permissions OWNER TO postgres;
@@ -0,0 +1,14 @@
+-- Start and end of every "view as" impersonation and each request made while it was active
+CREATE TABLE IF NOT EXISTS impersonation_events (
+    id                 SERIAL PRIMARY KEY,
+    impersonator_id    TEXT NOT NULL,
+    target_user_id     TEXT NOT NULL,
+    action             TEXT NOT NULL,
+    method             TEXT,
+    path               TEXT,
+    timestamp          TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS impersonation_events_timestamp_idx ON impersonation_events (timestamp);
+
+ALTER TABLE IF EXISTS impersonation_events OWNER TO postgres;
@@ -0,0 +1,18 @@
+-- Logins, logouts, session revocations, role changes and api key use, kept for security audits
+CREATE TABLE IF NOT EXISTS auth_events (
+    id                 SERIAL PRIMARY KEY,
+    event              TEXT NOT NULL,
+    discord_user_id    TEXT,
+    actor_id           TEXT,
+    reason             TEXT,
+    details            JSONB,
+    ip                 TEXT,
+    user_agent         TEXT,
+    request_id         TEXT,
+    timestamp          TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS auth_events_discord_user_id_idx ON auth_events (discord_user_id, timestamp);
+CREATE INDEX IF NOT EXISTS auth_events_timestamp_idx ON auth_events (timestamp);
+
+ALTER TABLE IF EXISTS auth_events OWNER TO postgres;
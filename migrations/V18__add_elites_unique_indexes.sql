@@ -0,0 +1,24 @@
+-- A player and a discord account can only belong to one elite, also when two creates run at the same time.
+-- Duplicates in the existing roster can not be merged automatically, so the migration stops and lists them for a manual cleanup.
+DO $$
+DECLARE
+    duplicates TEXT;
+BEGIN
+    SELECT
+        string_agg(duplicate, ', ')
+    INTO
+        duplicates
+    FROM (
+        SELECT 'minecraft_uuid ' || minecraft_uuid AS duplicate FROM elites GROUP BY minecraft_uuid HAVING count(*) > 1
+        UNION ALL
+        SELECT 'discord_user_id ' || discord_user_id AS duplicate FROM elites GROUP BY discord_user_id HAVING count(*) > 1
+    ) AS d;
+
+    IF duplicates IS NOT NULL THEN
+        RAISE EXCEPTION 'Several elites share %. Remove or merge the duplicate elites before starting the api again.', duplicates;
+    END IF;
+END
+$$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS elites_minecraft_uuid_idx ON elites (minecraft_uuid);
+CREATE UNIQUE INDEX IF NOT EXISTS elites_discord_user_id_idx ON elites (discord_user_id);
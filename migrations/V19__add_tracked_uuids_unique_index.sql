@@ -0,0 +1,8 @@
+-- A uuid is tracked at most once, concurrent adds of the same uuid insert a single row.
+-- Rows of a uuid that was added twice are interchangeable, only the first one is kept.
+DELETE FROM tracked_uuids t
+USING tracked_uuids first
+WHERE t.uuid = first.uuid
+  AND t.id > first.id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS tracked_uuids_uuid_idx ON tracked_uuids (uuid);
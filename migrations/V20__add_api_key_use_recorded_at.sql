@@ -0,0 +1,5 @@
+-- Last time the use of an api key was written to auth_events, uses are recorded at most once an hour per key
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS use_recorded_at TIMESTAMPTZ;
+
+-- The auth event filter matches a user as subject or actor
+CREATE INDEX IF NOT EXISTS auth_events_actor_id_idx ON auth_events (actor_id, timestamp);
@@ -0,0 +1,16 @@
+CREATE OR REPLACE VIEW tracked_uuids_with_ign AS
+SELECT
+    tu.*,
+    nh.ign
+FROM
+    tracked_uuids tu
+LEFT JOIN LATERAL (
+    SELECT ign
+    FROM name_history
+    WHERE uuid = tu.uuid
+    ORDER BY timestamp DESC
+    LIMIT 1
+) nh ON true
+ORDER BY tu.id;
+
+ALTER VIEW IF EXISTS tracked_uuids_with_ign OWNER TO postgres;
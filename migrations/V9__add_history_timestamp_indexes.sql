@@ -0,0 +1,6 @@
+-- Indexes backing the keyset paginated recent changes feed and the per-uuid history lookups
+CREATE INDEX IF NOT EXISTS name_history_uuid_timestamp_idx ON name_history (uuid, timestamp, id);
+CREATE INDEX IF NOT EXISTS name_history_timestamp_idx ON name_history (timestamp, id);
+CREATE INDEX IF NOT EXISTS skin_history_uuid_timestamp_idx ON skin_history (uuid, timestamp, id);
+CREATE INDEX IF NOT EXISTS skin_history_timestamp_idx ON skin_history (timestamp, id);
+CREATE INDEX IF NOT EXISTS tracked_uuids_history_timestamp_idx ON tracked_uuids_history (timestamp, id);
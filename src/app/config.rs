@@ -1,5 +1,7 @@
 use config::{Case, Config, Environment};
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde::de::value::{Error as ValueError, MapDeserializer};
 use serde_inline_default::serde_inline_default;
 use std::fmt::Error;
 
@@ -10,6 +12,10 @@ pub struct AppConfig {
     pub discord: DiscordConfig,
     pub redis: RedisConfig,
     pub session: SessionConfig,
+    #[serde(default = "section_defaults")]
+    pub tracker: TrackerConfig,
+    #[serde(default = "section_defaults")]
+    pub minecraft: MinecraftConfig,
 }
 
 #[serde_inline_default]
@@ -41,6 +47,8 @@ pub struct DiscordConfig {
     pub elite_guild_id: String,
     pub elite_staff_role_id: String,
     pub elite_role_id: String,
+    /// Origin of the frontend window that opens the login popup, the only origin the login result is posted to
+    pub frontend_origin: String,
 }
 
 #[serde_inline_default]
@@ -53,6 +61,55 @@ pub struct RedisConfig {
 #[derive(Deserialize, Clone)]
 pub struct SessionConfig {
     pub secure_cookie: bool,
+    /// Seconds a session trusts its role before the guild membership is checked again
+    #[serde_inline_default(900)]
+    pub role_check_interval_secs: i64,
+}
+
+#[serde_inline_default]
+#[derive(Deserialize, Clone)]
+pub struct TrackerConfig {
+    /// Base url of a Mojang compatible session server
+    #[serde_inline_default(String::from("https://sessionserver.mojang.com"))]
+    pub profile_api_url: String,
+    /// Base url of a Mojang compatible api used to resolve igns to uuids, e.g. `https://api.mojang.com`
+    #[serde_inline_default(String::from("https://api.mojang.com"))]
+    pub name_api_url: String,
+    /// Base url skin textures are downloaded from
+    #[serde_inline_default(String::from("https://textures.minecraft.net"))]
+    pub textures_url: String,
+    /// Seconds to wait between two polling rounds
+    #[serde_inline_default(300)]
+    pub poll_interval_secs: u64,
+    /// Amount of tracked uuids checked per polling round
+    #[serde_inline_default(20)]
+    pub batch_size: i64,
+    /// Milliseconds to wait between two profile requests to stay below the api rate limit
+    #[serde_inline_default(1000)]
+    pub request_delay_ms: u64,
+}
+
+/// Settings of the exports pulled by the game servers, every field is optional
+#[serde_inline_default]
+#[derive(Deserialize, Clone)]
+pub struct MinecraftConfig {
+    /// Permission level of staff in `ops.json`
+    #[serde_inline_default(4)]
+    pub op_level: u8,
+    /// LuckPerms group per elite status
+    #[serde_inline_default(String::from("staff"))]
+    pub group_staff: String,
+    #[serde_inline_default(String::from("veteran"))]
+    pub group_veteran: String,
+    #[serde_inline_default(String::from("elite"))]
+    pub group_elite: String,
+    #[serde_inline_default(String::from("trial"))]
+    pub group_trial: String,
+}
+
+/// Builds a config section from the defaults of its fields, for deployments that set none of them
+fn section_defaults<T: DeserializeOwned>() -> T {
+    T::deserialize(MapDeserializer::<_, ValueError>::new(std::iter::empty::<(&str, &str)>())).expect("Config section has a field without a default")
 }
 
 impl AppConfig {
@@ -67,3 +124,25 @@ impl AppConfig {
         Ok(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracker_config_defaults_every_field() {
+        let tracker: TrackerConfig = section_defaults();
+
+        assert_eq!(tracker.profile_api_url, "https://sessionserver.mojang.com");
+        assert_eq!(tracker.name_api_url, "https://api.mojang.com");
+        assert_eq!(tracker.poll_interval_secs, 300);
+    }
+
+    #[test]
+    fn minecraft_config_defaults_every_field() {
+        let minecraft: MinecraftConfig = section_defaults();
+
+        assert_eq!(minecraft.op_level, 4);
+        assert_eq!(minecraft.group_trial, "trial");
+    }
+}
@@ -1,16 +1,40 @@
 /// Five minutes in seconds
 pub const FIVE_MINUTES: i64 = 60 * 5;
+/// One minute in seconds
+pub const ONE_MINUTE: i64 = 60;
 /// One month in seconds
 pub const ONE_MONTH: i64 = 60 * 60 * 24 * 30;
 
 pub const CSRF_TOKEN_KEY: &str = "csrf_token";
+pub const PKCE_VERIFIER_KEY: &str = "pkce_verifier";
 pub const USER_ID_KEY: &str = "user_id";
 pub const USER_ROLE_KEY: &str = "user_role";
+pub const ROLE_CHECKED_AT_KEY: &str = "role_checked_at";
+pub const ROLE_CHECK_RETRY_AT_KEY: &str = "role_check_retry_at";
+pub const PERMISSIONS_KEY: &str = "permissions";
+pub const IMPERSONATION_USER_ID_KEY: &str = "impersonation_user_id";
+pub const IMPERSONATION_ROLE_KEY: &str = "impersonation_role";
+pub const IMPERSONATION_PERMISSIONS_KEY: &str = "impersonation_permissions";
+pub const IMPERSONATION_EXPIRES_AT_KEY: &str = "impersonation_expires_at";
+pub const CREATED_AT_KEY: &str = "created_at";
+pub const LAST_SEEN_AT_KEY: &str = "last_seen_at";
+pub const IP_KEY: &str = "ip";
+pub const USER_AGENT_KEY: &str = "user_agent";
 pub const DISCORD_ACCESS_TOKEN_KEY: &str = "discord_access_token";
 pub const DISCORD_REFRESH_TOKEN_KEY: &str = "discord_refresh_token";
 
 pub const SESSION_COOKIE_NAME: &str = "elite-sid";
 pub const SESSION_KEY_PREFIX: &str = "session";
+/// Set of the session ids of a user
+pub const USER_SESSIONS_KEY_PREFIX: &str = "user_sessions";
+/// Lock of a session while its discord tokens are refreshed
+pub const REFRESH_LOCK_KEY_PREFIX: &str = "refresh_lock";
+pub const AVATAR_KEY_PREFIX: &str = "avatar";
+/// Every avatar size is rendered and cached separately, so only a few are offered to keep the cache small
+pub const AVATAR_SIZES: [u32; 6] = [16, 32, 64, 128, 256, 512];
 
 pub const RAILWAY_REQUEST_ID_HEADER: &str = "X-Railway-Request-Id";
 pub const LOCAL_REQUEST_ID_HEADER: &str = "X-Request-Id";
+pub const FORWARDED_FOR_HEADER: &str = "X-Forwarded-For";
+pub const NEXT_CURSOR_HEADER: &str = "X-Next-Cursor";
+pub const TOTAL_COUNT_HEADER: &str = "X-Total-Count";
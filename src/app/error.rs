@@ -2,6 +2,7 @@ use axum::Json;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use serde_json::json;
+use std::collections::BTreeMap;
 
 pub type Result<T> = core::result::Result<T, AppError>;
 
@@ -12,21 +13,37 @@ pub enum AppError {
     InternalServerError,
     BadRequest(Option<String>),
     Unauthorized,
+    /// The request is authenticated but not allowed
+    Forbidden(Option<String>),
+    Conflict(Option<String>),
+    PreconditionFailed(Option<String>),
+    /// Validation messages keyed by field name
+    InvalidFields(BTreeMap<String, String>),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, message) = match &self {
-            AppError::NotFound(Some(msg)) => (StatusCode::NOT_FOUND, Some(msg.clone())),
-            AppError::NotFound(None) => (StatusCode::NOT_FOUND, Some("Resource Not Found".to_string())),
+        let (status, body) = match &self {
+            AppError::NotFound(Some(msg)) => (StatusCode::NOT_FOUND, Some(json!({ "error": msg }))),
+            AppError::NotFound(None) => (StatusCode::NOT_FOUND, Some(json!({ "error": "Resource Not Found" }))),
             AppError::BadRequest(None) => (StatusCode::BAD_REQUEST, None),
-            AppError::BadRequest(Some(msg)) => (StatusCode::BAD_REQUEST, Some(msg.clone())),
+            AppError::BadRequest(Some(msg)) => (StatusCode::BAD_REQUEST, Some(json!({ "error": msg }))),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, None),
+            AppError::Forbidden(None) => (StatusCode::FORBIDDEN, None),
+            AppError::Forbidden(Some(msg)) => (StatusCode::FORBIDDEN, Some(json!({ "error": msg }))),
+            AppError::Conflict(None) => (StatusCode::CONFLICT, None),
+            AppError::Conflict(Some(msg)) => (StatusCode::CONFLICT, Some(json!({ "error": msg }))),
+            AppError::PreconditionFailed(None) => (StatusCode::PRECONDITION_FAILED, None),
+            AppError::PreconditionFailed(Some(msg)) => (StatusCode::PRECONDITION_FAILED, Some(json!({ "error": msg }))),
             AppError::InternalServerError => (StatusCode::INTERNAL_SERVER_ERROR, None),
+            AppError::InvalidFields(fields) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Some(json!({ "error": "Invalid fields", "fields": fields })),
+            ),
         };
 
-        let mut res = match message {
-            Some(msg) => (status, Json(json!({ "error": msg }))).into_response(),
+        let mut res = match body {
+            Some(body) => (status, Json(body)).into_response(),
             None => status.into_response(),
         };
 
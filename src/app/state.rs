@@ -1,6 +1,9 @@
 use crate::app::config::AppConfig;
 use crate::error::Error;
-use crate::service::{DiscordApiService, DiscordAuthService, EliteService, IgnTrackerService, SessionService};
+use crate::service::{
+    ApiKeyService, AuthEventService, AvatarService, CalendarService, DiscordApiService, DiscordAuthService, EliteService, IgnTrackerService,
+    ImpersonationService, MinecraftService, MojangApiService, RoleService, SessionService,
+};
 use axum::extract::FromRef;
 use axum_macros::FromRef;
 use deadpool_postgres::Pool;
@@ -12,6 +15,14 @@ pub struct AppState {
     pub session: SessionService,
     pub elite: EliteService,
     pub ign_tracker: IgnTrackerService,
+    pub mojang: MojangApiService,
+    pub avatar: AvatarService,
+    pub calendar: CalendarService,
+    pub minecraft: MinecraftService,
+    pub api_keys: ApiKeyService,
+    pub roles: RoleService,
+    pub impersonation: ImpersonationService,
+    pub auth_events: AuthEventService,
 }
 
 #[derive(Clone, FromRef)]
@@ -35,7 +46,7 @@ impl FromRef<AppState> for DiscordApiService {
 impl AppState {
     /// Initialize the application state with all required services.
     pub async fn initialize(db_pool: Pool, redis: ConnectionManager, config: &AppConfig) -> Result<Self, Error> {
-        let session = SessionService::new(&config.session, redis);
+        let session = SessionService::new(&config.session, redis.clone());
 
         let elite = EliteService::new(db_pool.clone());
 
@@ -43,6 +54,15 @@ impl AppState {
         let discord_auth = DiscordAuthService::new(&config.discord);
 
         let ign_tracker = IgnTrackerService::new(db_pool.clone());
+        let mojang = MojangApiService::new(&config.tracker);
+        let avatar = AvatarService::new(&config.tracker, redis);
+
+        let calendar = CalendarService::new(db_pool.clone());
+        let minecraft = MinecraftService::new(db_pool.clone(), &config.minecraft);
+        let api_keys = ApiKeyService::new(db_pool.clone());
+        let roles = RoleService::new(db_pool.clone());
+        let impersonation = ImpersonationService::new(db_pool.clone());
+        let auth_events = AuthEventService::new(db_pool);
 
         Ok(Self {
             discord: DiscordState {
@@ -52,6 +72,14 @@ impl AppState {
             session,
             elite,
             ign_tracker,
+            mojang,
+            avatar,
+            calendar,
+            minecraft,
+            api_keys,
+            roles,
+            impersonation,
+            auth_events,
         })
     }
 }
@@ -1,6 +1,7 @@
 use crate::app::error::AppError;
 use tracing::trace;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum DbError {
     ConnectionError,
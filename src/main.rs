@@ -3,6 +3,7 @@ mod db;
 mod error;
 mod model;
 mod service;
+mod task;
 mod web;
 
 use app::config::AppConfig;
@@ -23,6 +24,8 @@ async fn main() {
 
     let state = AppState::initialize(db_pool.clone(), redis, &config).await.expect("Failed to initialize app state");
 
+    task::spawn_ign_tracker(state.ign_tracker.clone(), state.mojang.clone(), &config.tracker);
+
     let listener_url = format!("{}:{}", &config.server.address, &config.server.port);
     let listener = tokio::net::TcpListener::bind(&listener_url).await.unwrap();
 
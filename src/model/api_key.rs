@@ -0,0 +1,134 @@
+use crate::model::elite::validation::FieldErrors;
+use crate::model::permission::Permission;
+use crate::model::session::SessionUser;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use tokio_postgres::Row;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ApiKey {
+    pub id: i32,
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    /// Discord id of the staff member that created the key
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    /// Id used as the session user id and as the actor in audit entries, so changes can be traced back to the key
+    pub fn actor_id(&self) -> String {
+        format!("api-key:{}", self.id)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApiKeyForCreate {
+    pub name: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKeyForCreate {
+    pub fn validate(&mut self) -> Result<(), FieldErrors> {
+        let mut errors = FieldErrors::new();
+
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() || self.name.len() > 64 {
+            errors.insert("name".to_string(), "must be between 1 and 64 characters".to_string());
+        }
+        if self.scopes.is_empty() {
+            errors.insert("scopes".to_string(), "must contain at least one scope".to_string());
+        }
+        if self.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+            errors.insert("expires_at".to_string(), "must be in the future".to_string());
+        }
+
+        self.scopes.sort_by_key(|scope| scope.to_string());
+        self.scopes.dedup();
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+impl From<&Row> for ApiKey {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            name: row.get("name"),
+            scopes: row.get::<_, Vec<String>>("scopes").iter().filter_map(|scope| scope.parse().ok()).collect(),
+            created_by: row.get("created_by"),
+            created_at: row.get("created_at"),
+            expires_at: row.get("expires_at"),
+            revoked_at: row.get("revoked_at"),
+            last_used_at: row.get("last_used_at"),
+        }
+    }
+}
+
+#[derive(EnumString, Display, Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum ApiKeyScope {
+    #[strum(serialize = "elites:read")]
+    #[serde(rename = "elites:read")]
+    ElitesRead,
+    #[strum(serialize = "elites:write")]
+    #[serde(rename = "elites:write")]
+    ElitesWrite,
+    #[strum(serialize = "tracker:read")]
+    #[serde(rename = "tracker:read")]
+    TrackerRead,
+    #[strum(serialize = "tracker:write")]
+    #[serde(rename = "tracker:write")]
+    TrackerWrite,
+}
+
+impl ApiKeyScope {
+    /// Permissions granted to keys holding the scope, checked by `mw_permission` the same way as those of users
+    pub fn permissions(&self) -> Vec<Permission> {
+        match self {
+            ApiKeyScope::ElitesRead => vec![],
+            ApiKeyScope::ElitesWrite => vec![Permission::RosterEdit],
+            ApiKeyScope::TrackerRead | ApiKeyScope::TrackerWrite => vec![Permission::TrackerManage],
+        }
+    }
+
+    /// A key can only be given scopes whose permissions its creator holds, so minting a key never escalates privileges
+    pub fn is_grantable_by(&self, user: &SessionUser) -> bool {
+        self.permissions().into_iter().all(|permission| user.has_permission(permission))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::session::UserRole;
+
+    #[test]
+    fn read_scope_grants_no_edit_permission() {
+        assert!(!ApiKeyScope::ElitesRead.permissions().contains(&Permission::RosterEdit));
+        assert!(ApiKeyScope::ElitesWrite.permissions().contains(&Permission::RosterEdit));
+    }
+
+    #[test]
+    fn scope_is_only_grantable_with_its_permissions() {
+        let user = SessionUser {
+            id: String::from("1"),
+            role: UserRole::Elite,
+            permissions: vec![Permission::ApiKeysManage, Permission::TrackerManage],
+        };
+
+        assert!(ApiKeyScope::ElitesRead.is_grantable_by(&user));
+        assert!(ApiKeyScope::TrackerWrite.is_grantable_by(&user));
+        assert!(!ApiKeyScope::ElitesWrite.is_grantable_by(&user));
+    }
+
+    #[test]
+    fn scopes_parse_from_their_names() {
+        assert_eq!("tracker:write".parse::<ApiKeyScope>().ok(), Some(ApiKeyScope::TrackerWrite));
+        assert!("tracker:admin".parse::<ApiKeyScope>().is_err());
+    }
+}
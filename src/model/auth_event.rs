@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use strum_macros::{Display, EnumString};
+use tokio_postgres::Row;
+
+#[derive(EnumString, Display, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthEventKind {
+    #[strum(serialize = "login_success")]
+    LoginSuccess,
+    /// `reason` is the [`AuthFailure`](crate::web::auth_page::AuthFailure) shown to the user, e.g. `invalid-state` for a csrf mismatch
+    #[strum(serialize = "login_failure")]
+    LoginFailure,
+    #[strum(serialize = "logout")]
+    Logout,
+    /// Revoked by the user or staff, or because the role check found the user is no longer an elite
+    #[strum(serialize = "session_revoked")]
+    SessionRevoked,
+    /// Role or permissions changed on the periodic role check, `details` holds the `before` and `after` values
+    #[strum(serialize = "role_changed")]
+    RoleChanged,
+    /// First use of an api key within an hour
+    #[strum(serialize = "api_key_used")]
+    ApiKeyUsed,
+}
+
+/// Where the request causing an event came from
+#[derive(Debug, Clone, Default)]
+pub struct RequestOrigin {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct AuthEventForCreate {
+    pub event: AuthEventKind,
+    /// User the event is about, unknown for logins failing before discord identified the user
+    pub discord_user_id: Option<String>,
+    /// Who caused the event if it was not the user themselves, e.g. staff revoking sessions
+    pub actor_id: Option<String>,
+    pub reason: Option<String>,
+    pub details: Option<Value>,
+}
+
+impl AuthEventForCreate {
+    pub fn new(event: AuthEventKind, discord_user_id: Option<&str>) -> Self {
+        Self {
+            event,
+            discord_user_id: discord_user_id.map(str::to_string),
+            actor_id: None,
+            reason: None,
+            details: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct AuthEvent {
+    pub id: i32,
+    pub event: String,
+    pub discord_user_id: Option<String>,
+    pub actor_id: Option<String>,
+    pub reason: Option<String>,
+    pub details: Option<Value>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<&Row> for AuthEvent {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            event: row.get("event"),
+            discord_user_id: row.get("discord_user_id"),
+            actor_id: row.get("actor_id"),
+            reason: row.get("reason"),
+            details: row.get("details"),
+            ip: row.get("ip"),
+            user_agent: row.get("user_agent"),
+            request_id: row.get("request_id"),
+            timestamp: row.get("timestamp"),
+        }
+    }
+}
+
+/// Filters of the auth event audit log, every given filter has to match
+#[derive(Debug, Default)]
+pub struct AuthEventFilter {
+    pub event: Option<AuthEventKind>,
+    /// Matches events about or caused by the user
+    pub discord_user_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+#[allow(dead_code)] // Unused for now
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RoleTag {
     pub bot_id: Option<String>,                  // The ID of the bot this role belongs to
@@ -0,0 +1,84 @@
+use crate::model::elite::Elite;
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use uuid::Uuid;
+
+#[derive(Serialize, Debug)]
+pub struct UpcomingBirthday {
+    pub elite_id: i32,
+    pub minecraft_uuid: Uuid,
+    pub ign: Option<String>,
+    pub birthday: NaiveDate,
+    pub next_birthday: NaiveDate,
+    /// Age the elite turns on `next_birthday`
+    pub age: i32,
+    pub days_until: i64,
+}
+
+impl UpcomingBirthday {
+    /// Returns the next birthday of an elite on or after `today`, `None` if no birthday is set
+    pub fn for_elite(elite: &Elite, today: NaiveDate) -> Option<Self> {
+        let birthday = elite.birthday?;
+        let next_birthday = next_birthday(birthday, today);
+
+        Some(Self {
+            elite_id: elite.id,
+            minecraft_uuid: elite.minecraft_uuid,
+            ign: elite.ign.clone(),
+            birthday,
+            next_birthday,
+            age: next_birthday.year() - birthday.year(),
+            days_until: (next_birthday - today).num_days(),
+        })
+    }
+}
+
+/// Next occurrence of a birthday on or after `today`, wrapping into the next year if it already passed
+pub fn next_birthday(birthday: NaiveDate, today: NaiveDate) -> NaiveDate {
+    let this_year = birthday_in(birthday, today.year());
+
+    if this_year >= today {
+        this_year
+    } else {
+        birthday_in(birthday, today.year() + 1)
+    }
+}
+
+/// Occurrence of a birthday in the given year. Feb 29 birthdays are celebrated on Feb 28 in common years.
+fn birthday_in(birthday: NaiveDate, year: i32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, birthday.month(), birthday.day())
+        .or_else(|| NaiveDate::from_ymd_opt(year, 2, 28))
+        .expect("Feb 28 exists in every year")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn next_birthday_is_today_if_it_is_today() {
+        assert_eq!(next_birthday(date(2000, 6, 15), date(2025, 6, 15)), date(2025, 6, 15));
+    }
+
+    #[test]
+    fn next_birthday_wraps_into_next_year() {
+        assert_eq!(next_birthday(date(2000, 1, 3), date(2025, 12, 30)), date(2026, 1, 3));
+        assert_eq!(next_birthday(date(2000, 12, 31), date(2025, 12, 30)), date(2025, 12, 31));
+    }
+
+    #[test]
+    fn leap_day_birthday_is_kept_in_leap_years() {
+        assert_eq!(birthday_in(date(2000, 2, 29), 2028), date(2028, 2, 29));
+        assert_eq!(next_birthday(date(2000, 2, 29), date(2027, 12, 1)), date(2028, 2, 29));
+    }
+
+    #[test]
+    fn leap_day_birthday_moves_to_feb_28_in_common_years() {
+        assert_eq!(birthday_in(date(2000, 2, 29), 2025), date(2025, 2, 28));
+        assert_eq!(next_birthday(date(2000, 2, 29), date(2025, 3, 1)), date(2026, 2, 28));
+    }
+}
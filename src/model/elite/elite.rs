@@ -1,5 +1,7 @@
+use crate::model::elite::validation::{FieldErrors, parse_country_code, validate_birthday, validate_discord_snowflake};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::error::Error;
 use strum_macros::EnumString;
 use tokio_postgres::Row;
@@ -17,18 +19,94 @@ pub struct Elite {
     pub status: EliteStatus,
     pub country_code: String,
     pub birthday: Option<NaiveDate>,
+    /// Row version, incremented on every update
+    pub version: i32,
 }
 
-// TODO - maybe implement a custom `FromRequest` trait for this struct or something - especially for `status` validation & invalid/unknown fields
-#[derive(Deserialize, Debug)]
+/// Partial update of an elite with JSON Merge Patch semantics.
+/// `None` means the field was not provided, `Some(None)` for `birthday` means it was explicitly set to `null`.
+#[derive(Debug, Default)]
 pub struct EliteForUpdate {
     pub minecraft_uuid: Option<Uuid>,
     pub discord_user_id: Option<String>,
     pub status: Option<EliteStatus>,
     pub country_code: Option<String>,
+    pub birthday: Option<Option<NaiveDate>>,
+}
+
+impl TryFrom<Map<String, Value>> for EliteForUpdate {
+    type Error = FieldErrors;
+
+    /// Validates every field of the patch body, rejecting unknown fields and `null` for non nullable fields
+    fn try_from(body: Map<String, Value>) -> Result<Self, Self::Error> {
+        let mut update = Self::default();
+        let mut errors = FieldErrors::new();
+
+        for (field, value) in body {
+            let result = match (field.as_str(), value) {
+                ("minecraft_uuid", Value::String(value)) => {
+                    Uuid::parse_str(&value).map(|uuid| update.minecraft_uuid = Some(uuid)).map_err(|_| "must be a uuid".to_string())
+                }
+                ("discord_user_id", Value::String(value)) => validate_discord_snowflake(&value).map(|_| update.discord_user_id = Some(value)),
+                ("status", Value::String(value)) => value
+                    .parse::<EliteStatus>()
+                    .map(|status| update.status = Some(status))
+                    .map_err(|_| "must be one of staff, veteran, elite, trial, none".to_string()),
+                ("country_code", Value::String(value)) => parse_country_code(&value).map(|country_code| update.country_code = Some(country_code)),
+                ("birthday", Value::Null) => {
+                    update.birthday = Some(None);
+                    Ok(())
+                }
+                ("birthday", Value::String(value)) => NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                    .map_err(|_| "must be a date formatted as YYYY-MM-DD".to_string())
+                    .and_then(|birthday| validate_birthday(&birthday).map(|_| update.birthday = Some(Some(birthday)))),
+                ("minecraft_uuid" | "discord_user_id" | "status" | "country_code", Value::Null) => Err("must not be null".to_string()),
+                ("minecraft_uuid" | "discord_user_id" | "status" | "country_code" | "birthday", _) => Err("must be a string".to_string()),
+                _ => Err("unknown field".to_string()),
+            };
+
+            if let Err(message) = result {
+                errors.insert(field, message);
+            }
+        }
+
+        if errors.is_empty() { Ok(update) } else { Err(errors) }
+    }
+}
+
+/// Either `minecraft_uuid` or `ign` has to be provided
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EliteForCreate {
+    pub minecraft_uuid: Option<Uuid>,
+    pub ign: Option<String>,
+    pub discord_user_id: String,
+    pub status: EliteStatus,
+    pub country_code: String,
     pub birthday: Option<NaiveDate>,
 }
 
+impl EliteForCreate {
+    /// Validates the fields and normalizes the country code
+    pub fn validate(&mut self) -> Result<(), FieldErrors> {
+        let mut errors = FieldErrors::new();
+
+        if let Err(message) = validate_discord_snowflake(&self.discord_user_id) {
+            errors.insert("discord_user_id".to_string(), message);
+        }
+        match parse_country_code(&self.country_code) {
+            Ok(country_code) => self.country_code = country_code,
+            Err(message) => {
+                errors.insert("country_code".to_string(), message);
+            }
+        }
+        if let Some(Err(message)) = self.birthday.as_ref().map(validate_birthday) {
+            errors.insert("birthday".to_string(), message);
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
 impl From<&Row> for Elite {
     fn from(row: &Row) -> Self {
         Self {
@@ -40,6 +118,7 @@ impl From<&Row> for Elite {
             status: row.get("status"),
             country_code: row.get("country_code"),
             birthday: row.get::<_, Option<NaiveDate>>("birthday"),
+            version: row.get("version"),
         }
     }
 }
@@ -160,3 +239,41 @@ impl<'a> FromSql<'a> for EliteStatus {
 // }
 
 // impl TryFrom
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patch(body: Value) -> Result<EliteForUpdate, FieldErrors> {
+        match body {
+            Value::Object(body) => EliteForUpdate::try_from(body),
+            _ => panic!("patch body must be an object"),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_patch() {
+        let update = patch(json!({ "discord_user_id": "123456789012345678", "birthday": null })).unwrap();
+
+        assert_eq!(update.discord_user_id.as_deref(), Some("123456789012345678"));
+        assert_eq!(update.birthday, Some(None));
+        assert_eq!(update.minecraft_uuid, None);
+    }
+
+    #[test]
+    fn rejects_null_for_non_nullable_fields() {
+        let errors = patch(json!({ "status": null })).unwrap_err();
+
+        assert_eq!(errors.get("status").map(String::as_str), Some("must not be null"));
+    }
+
+    #[test]
+    fn collects_every_field_error() {
+        let errors = patch(json!({ "minecraft_uuid": "nope", "country_code": 1, "nickname": "x" })).unwrap_err();
+
+        assert_eq!(errors.get("minecraft_uuid").map(String::as_str), Some("must be a uuid"));
+        assert_eq!(errors.get("country_code").map(String::as_str), Some("must be a string"));
+        assert_eq!(errors.get("nickname").map(String::as_str), Some("unknown field"));
+    }
+}
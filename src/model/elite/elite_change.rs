@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use tokio_postgres::Row;
+
+/// An audit entry for a created or updated elite.
+/// `changes` maps each changed column to its `before` and `after` value, `before` is `null` for created elites.
+#[derive(Serialize, Debug)]
+pub struct EliteChange {
+    pub id: i32,
+    pub elite_id: i32,
+    pub actor_discord_id: String,
+    pub changes: Value,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<&Row> for EliteChange {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            elite_id: row.get("elite_id"),
+            actor_discord_id: row.get("actor_discord_id"),
+            changes: row.get("changes"),
+            timestamp: row.get("timestamp"),
+        }
+    }
+}
+
+impl EliteChange {
+    /// Diffs two json objects of an `elites` row, ignoring the `id` and `version` columns
+    pub fn diff(before: &Value, after: &Value) -> Map<String, Value> {
+        let empty = Map::new();
+        let before = before.as_object().unwrap_or(&empty);
+        let after = after.as_object().unwrap_or(&empty);
+
+        after
+            .iter()
+            .filter(|(field, _)| !matches!(field.as_str(), "id" | "version"))
+            .filter_map(|(field, after_value)| {
+                let before_value = before.get(field).unwrap_or(&Value::Null);
+                (before_value != after_value).then(|| (field.clone(), json!({ "before": before_value, "after": after_value })))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_contains_only_changed_fields() {
+        let before = json!({ "id": 1, "version": 1, "status": "trial", "country_code": "DE" });
+        let after = json!({ "id": 1, "version": 2, "status": "elite", "country_code": "DE" });
+
+        let changes = EliteChange::diff(&before, &after);
+
+        assert_eq!(Value::Object(changes), json!({ "status": { "before": "trial", "after": "elite" } }));
+    }
+
+    #[test]
+    fn diff_of_created_elite_has_null_before_values() {
+        let after = json!({ "id": 1, "version": 1, "status": "trial", "birthday": null });
+
+        let changes = EliteChange::diff(&Value::Null, &after);
+
+        // A column that is null after creation did not change
+        assert_eq!(Value::Object(changes), json!({ "status": { "before": null, "after": "trial" } }));
+    }
+}
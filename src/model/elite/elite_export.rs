@@ -0,0 +1,66 @@
+use crate::model::elite::Elite;
+use serde::Deserialize;
+use tokio_postgres::Row;
+
+/// Columns that can be selected for an export, in their default order
+pub const ELITE_EXPORT_COLUMNS: [&str; 8] = [
+    "id",
+    "minecraft_uuid",
+    "ign",
+    "discord_user_id",
+    "status",
+    "country_code",
+    "birthday",
+    "being_tracked",
+];
+
+/// Column holding the chronological list of igns, only exported on request
+pub const NAME_HISTORY_COLUMN: &str = "name_history";
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Json,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EliteExportRow {
+    pub elite: Elite,
+    /// Igns in chronological order, `None` if the name history was not requested
+    pub name_history: Option<Vec<String>>,
+}
+
+impl From<&Row> for EliteExportRow {
+    fn from(row: &Row) -> Self {
+        let name_history = row.get::<_, Option<Vec<String>>>("name_history").map(|mut names| {
+            names.dedup();
+            names
+        });
+
+        Self {
+            elite: Elite::from(row),
+            name_history,
+        }
+    }
+}
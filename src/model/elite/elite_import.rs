@@ -0,0 +1,342 @@
+use crate::model::elite::validation::FieldErrors;
+use crate::model::elite::{ELITE_EXPORT_COLUMNS, Elite, EliteChange, EliteForCreate, EliteForUpdate, NAME_HISTORY_COLUMN};
+use csv::{ReaderBuilder, Trim};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Columns an import can set, matching the fields of [`EliteForUpdate`]
+pub const ELITE_IMPORT_COLUMNS: [&str; 5] = ["minecraft_uuid", "discord_user_id", "status", "country_code", "birthday"];
+
+#[derive(Debug)]
+pub struct ImportRow {
+    /// Line of the row in the csv, used to point at the row in the report
+    pub line: u64,
+    /// Non empty cells of the importable columns
+    pub fields: Map<String, Value>,
+}
+
+/// Parses a csv with a header row. Empty cells leave the field unchanged.
+/// Export only columns like `id` or `ign` are ignored, so an export can be edited and imported again.
+pub fn parse_import_csv(csv: &str) -> Result<Vec<ImportRow>, String> {
+    // Spreadsheet applications like to prefix their csv exports with a byte order mark
+    let csv = csv.trim_start_matches('\u{feff}');
+
+    let mut reader = ReaderBuilder::new().trim(Trim::All).from_reader(csv.as_bytes());
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+
+    if let Some(unknown) = headers
+        .iter()
+        .find(|column| !ELITE_IMPORT_COLUMNS.contains(column) && !ELITE_EXPORT_COLUMNS.contains(column) && *column != NAME_HISTORY_COLUMN)
+    {
+        return Err(format!("unknown column {unknown}"));
+    }
+    if !headers.iter().any(|column| column == "minecraft_uuid" || column == "discord_user_id") {
+        return Err("a minecraft_uuid or discord_user_id column is required".to_string());
+    }
+
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| e.to_string())?;
+
+        let fields = headers
+            .iter()
+            .zip(record.iter())
+            .filter(|(column, value)| ELITE_IMPORT_COLUMNS.contains(column) && !value.is_empty())
+            .map(|(column, value)| (column.to_string(), Value::String(value.to_string())))
+            .collect();
+
+        rows.push(ImportRow {
+            line: record.position().map(|position| position.line()).unwrap_or_default(),
+            fields,
+        });
+    }
+
+    Ok(rows)
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub applied: bool,
+    pub creates: Vec<ImportCreate>,
+    pub updates: Vec<ImportUpdate>,
+    pub conflicts: Vec<ImportConflict>,
+    /// Rows matching an elite without changing any field
+    pub unchanged: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportCreate {
+    pub line: u64,
+    #[serde(flatten)]
+    pub elite: EliteForCreate,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportUpdate {
+    pub line: u64,
+    pub elite_id: i32,
+    /// Changed fields with their `before` and `after` values, in the format of [`EliteChange::changes`]
+    pub changes: Map<String, Value>,
+    /// Version of the elite the diff was made against, the update fails if the elite changed in the meantime
+    #[serde(skip)]
+    pub version: i32,
+    #[serde(skip)]
+    pub update: EliteForUpdate,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImportConflict {
+    pub line: u64,
+    pub reason: String,
+    #[serde(skip_serializing_if = "FieldErrors::is_empty")]
+    pub fields: FieldErrors,
+}
+
+impl ImportConflict {
+    pub fn new(line: u64, reason: impl Into<String>) -> Self {
+        Self {
+            line,
+            reason: reason.into(),
+            fields: FieldErrors::new(),
+        }
+    }
+}
+
+impl ImportReport {
+    /// Matches every row to an existing elite by uuid or discord user id and diffs it against that elite.
+    /// Rows matching no elite become creates, their ign still has to be resolved before they can be applied.
+    pub fn plan(rows: Vec<ImportRow>, elites: &[Elite]) -> Self {
+        let mut report = Self::default();
+
+        // First line using a uuid or discord user id, a player or account can only appear once per import
+        let mut uuid_lines: HashMap<Uuid, u64> = HashMap::new();
+        let mut discord_user_id_lines: HashMap<String, u64> = HashMap::new();
+
+        for row in rows {
+            let update = match EliteForUpdate::try_from(row.fields) {
+                Ok(update) => update,
+                Err(fields) => {
+                    report.conflicts.push(ImportConflict {
+                        line: row.line,
+                        reason: "Invalid fields".to_string(),
+                        fields,
+                    });
+                    continue;
+                }
+            };
+
+            if update.minecraft_uuid.is_none() && update.discord_user_id.is_none() {
+                report.conflicts.push(ImportConflict::new(row.line, "Row has neither a minecraft_uuid nor a discord_user_id"));
+                continue;
+            }
+            if let Some(first) = update.minecraft_uuid.and_then(|uuid| uuid_lines.get(&uuid)) {
+                report.conflicts.push(ImportConflict::new(row.line, format!("minecraft_uuid is already used on line {first}")));
+                continue;
+            }
+            if let Some(first) = update.discord_user_id.as_ref().and_then(|discord_user_id| discord_user_id_lines.get(discord_user_id)) {
+                report.conflicts.push(ImportConflict::new(row.line, format!("discord_user_id is already used on line {first}")));
+                continue;
+            }
+            if let Some(uuid) = update.minecraft_uuid {
+                uuid_lines.insert(uuid, row.line);
+            }
+            if let Some(discord_user_id) = &update.discord_user_id {
+                discord_user_id_lines.insert(discord_user_id.clone(), row.line);
+            }
+
+            let by_uuid = update.minecraft_uuid.and_then(|uuid| elites.iter().find(|elite| elite.minecraft_uuid == uuid));
+            let by_discord_user_id = update
+                .discord_user_id
+                .as_ref()
+                .and_then(|discord_user_id| elites.iter().find(|elite| &elite.discord_user_id == discord_user_id));
+
+            match (by_uuid, by_discord_user_id) {
+                (Some(uuid_owner), Some(discord_owner)) if uuid_owner.id != discord_owner.id => {
+                    report.conflicts.push(ImportConflict::new(
+                        row.line,
+                        format!(
+                            "minecraft_uuid belongs to elite {} but discord_user_id belongs to elite {}",
+                            uuid_owner.id, discord_owner.id
+                        ),
+                    ));
+                }
+                (Some(elite), _) | (None, Some(elite)) => report.push_update(row.line, elite, update),
+                (None, None) => report.push_create(row.line, update),
+            }
+        }
+
+        report
+    }
+
+    fn push_update(&mut self, line: u64, elite: &Elite, update: EliteForUpdate) {
+        let before = serde_json::to_value(elite).unwrap_or_default();
+        let mut after = before.clone();
+
+        if let Some(after) = after.as_object_mut() {
+            if let Some(minecraft_uuid) = update.minecraft_uuid {
+                after.insert("minecraft_uuid".to_string(), json!(minecraft_uuid));
+            }
+            if let Some(discord_user_id) = &update.discord_user_id {
+                after.insert("discord_user_id".to_string(), json!(discord_user_id));
+            }
+            if let Some(status) = &update.status {
+                after.insert("status".to_string(), json!(status));
+            }
+            if let Some(country_code) = &update.country_code {
+                after.insert("country_code".to_string(), json!(country_code));
+            }
+            if let Some(birthday) = update.birthday {
+                after.insert("birthday".to_string(), json!(birthday));
+            }
+        }
+
+        let changes = EliteChange::diff(&before, &after);
+        if changes.is_empty() {
+            self.unchanged += 1;
+            return;
+        }
+
+        self.updates.push(ImportUpdate {
+            line,
+            elite_id: elite.id,
+            changes,
+            version: elite.version,
+            update,
+        });
+    }
+
+    fn push_create(&mut self, line: u64, update: EliteForUpdate) {
+        let mut missing = FieldErrors::new();
+        if update.minecraft_uuid.is_none() {
+            missing.insert("minecraft_uuid".to_string(), "is required for new elites".to_string());
+        }
+        if update.discord_user_id.is_none() {
+            missing.insert("discord_user_id".to_string(), "is required for new elites".to_string());
+        }
+        if update.status.is_none() {
+            missing.insert("status".to_string(), "is required for new elites".to_string());
+        }
+        if update.country_code.is_none() {
+            missing.insert("country_code".to_string(), "is required for new elites".to_string());
+        }
+
+        let (Some(minecraft_uuid), Some(discord_user_id), Some(status), Some(country_code)) =
+            (update.minecraft_uuid, update.discord_user_id, update.status, update.country_code)
+        else {
+            self.conflicts.push(ImportConflict {
+                line,
+                reason: "Row matches no elite and is missing fields to create one".to_string(),
+                fields: missing,
+            });
+            return;
+        };
+
+        self.creates.push(ImportCreate {
+            line,
+            elite: EliteForCreate {
+                minecraft_uuid: Some(minecraft_uuid),
+                ign: None,
+                discord_user_id,
+                status,
+                country_code,
+                birthday: update.birthday.flatten(),
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::elite::EliteStatus;
+
+    const UUID: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+    const DISCORD_USER_ID: &str = "123456789012345678";
+
+    fn elite() -> Elite {
+        Elite {
+            id: 1,
+            minecraft_uuid: Uuid::parse_str(UUID).unwrap(),
+            ign: Some(String::from("Notch")),
+            being_tracked: true,
+            discord_user_id: DISCORD_USER_ID.to_string(),
+            status: EliteStatus::Trial,
+            country_code: String::from("DE"),
+            birthday: None,
+            version: 3,
+        }
+    }
+
+    fn plan(csv: &str) -> ImportReport {
+        ImportReport::plan(parse_import_csv(csv).unwrap(), &[elite()])
+    }
+
+    #[test]
+    fn parse_skips_empty_cells_and_export_only_columns() {
+        let rows = parse_import_csv(&format!("\u{feff}id,ign,minecraft_uuid,status\n1,Notch,{UUID},\n")).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(Value::Object(rows[0].fields.clone()), json!({ "minecraft_uuid": UUID }));
+    }
+
+    #[test]
+    fn parse_rejects_unknown_columns_and_missing_identity() {
+        assert_eq!(parse_import_csv("minecraft_uuid,nickname\n").unwrap_err(), "unknown column nickname");
+        assert!(parse_import_csv("status,country_code\n").is_err());
+    }
+
+    #[test]
+    fn plan_diffs_matching_elites() {
+        let report = plan(&format!("minecraft_uuid,status,country_code\n{UUID},elite,DE\n"));
+
+        assert_eq!(report.updates.len(), 1);
+        assert_eq!(report.updates[0].elite_id, 1);
+        assert_eq!(report.updates[0].version, 3);
+        assert_eq!(
+            Value::Object(report.updates[0].changes.clone()),
+            json!({ "status": { "before": "trial", "after": "elite" } })
+        );
+    }
+
+    #[test]
+    fn plan_counts_rows_without_changes() {
+        let report = plan(&format!("discord_user_id,status\n{DISCORD_USER_ID},trial\n"));
+
+        assert_eq!(report.unchanged, 1);
+        assert!(report.updates.is_empty());
+    }
+
+    #[test]
+    fn plan_creates_unknown_elites_with_all_required_fields() {
+        let report = plan("minecraft_uuid,discord_user_id,status,country_code\n853c80ef-3c37-49fd-aa49-938b674adae6,876543210987654321,trial,US\n");
+
+        assert_eq!(report.creates.len(), 1);
+        assert!(report.conflicts.is_empty());
+    }
+
+    #[test]
+    fn plan_reports_conflicts() {
+        let report = plan(&format!(
+            "minecraft_uuid,discord_user_id,status\n\
+             {UUID},,nope\n\
+             {UUID},{DISCORD_USER_ID},\n\
+             853c80ef-3c37-49fd-aa49-938b674adae6,{DISCORD_USER_ID},\n\
+             ,876543210987654321,trial\n"
+        ));
+
+        let reasons: Vec<_> = report.conflicts.iter().map(|conflict| (conflict.line, conflict.reason.as_str())).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (2, "Invalid fields"),
+                (4, "discord_user_id is already used on line 3"),
+                (5, "Row matches no elite and is missing fields to create one"),
+            ]
+        );
+        assert_eq!(report.unchanged, 1);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::model::elite::{Elite, EliteStatus};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EliteSort {
+    #[default]
+    Id,
+    Ign,
+    Birthday,
+    Status,
+}
+
+impl EliteSort {
+    /// Non null sort expression on the `elites_with_ign` view, so the sort key can be compared in a keyset condition
+    pub fn sql_key(&self) -> &'static str {
+        match self {
+            EliteSort::Id => "e.id",
+            EliteSort::Ign => "lower(COALESCE(e.ign, ''))",
+            EliteSort::Birthday => "COALESCE(e.birthday, 'infinity'::date)",
+            EliteSort::Status => "array_position(ARRAY['staff', 'veteran', 'elite', 'trial', 'none'], e.status)",
+        }
+    }
+
+    /// Type the text encoded cursor key is cast to
+    pub fn sql_type(&self) -> &'static str {
+        match self {
+            EliteSort::Id | EliteSort::Status => "int",
+            EliteSort::Ign => "text",
+            EliteSort::Birthday => "date",
+        }
+    }
+
+    /// Value of [`Self::sql_key`] for an elite, encoded as text
+    fn key_of(&self, elite: &Elite) -> String {
+        match self {
+            EliteSort::Id => elite.id.to_string(),
+            EliteSort::Ign => elite.ign.as_deref().unwrap_or_default().to_lowercase(),
+            EliteSort::Birthday => elite.birthday.map(|birthday| birthday.to_string()).unwrap_or_else(|| "infinity".to_string()),
+            EliteSort::Status => {
+                let rank = match elite.status {
+                    EliteStatus::Staff => 1,
+                    EliteStatus::Veteran => 2,
+                    EliteStatus::Elite => 3,
+                    EliteStatus::Trial => 4,
+                    EliteStatus::None => 5,
+                };
+                rank.to_string()
+            }
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            EliteSort::Id => "id",
+            EliteSort::Ign => "ign",
+            EliteSort::Birthday => "birthday",
+            EliteSort::Status => "status",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+
+    /// Comparison operator selecting the rows after a cursor
+    pub fn sql_after(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EliteRosterFilter {
+    pub statuses: Vec<EliteStatus>,
+    /// Case insensitive prefix of a current or past ign
+    pub search: Option<String>,
+    pub country_code: Option<String>,
+    pub being_tracked: Option<bool>,
+    pub sort: EliteSort,
+    pub order: SortOrder,
+    pub cursor: Option<EliteRosterCursor>,
+}
+
+/// Position in the roster, ordered by the sort key and `id`.
+/// The cursor is only valid for the sort and order it was created with.
+#[derive(Debug, PartialEq)]
+pub struct EliteRosterCursor {
+    pub sort: EliteSort,
+    pub order: SortOrder,
+    pub key: String,
+    pub id: i32,
+}
+
+impl EliteRosterCursor {
+    pub fn after(elite: &Elite, sort: EliteSort, order: SortOrder) -> Self {
+        Self {
+            sort,
+            order,
+            key: sort.key_of(elite),
+            id: elite.id,
+        }
+    }
+
+    /// Encodes the cursor into an opaque url safe string
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}|{}|{}", self.sort.as_str(), self.order.sql(), self.id, self.key))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+        let mut parts = decoded.splitn(4, '|');
+
+        let sort = match parts.next()? {
+            "id" => EliteSort::Id,
+            "ign" => EliteSort::Ign,
+            "birthday" => EliteSort::Birthday,
+            "status" => EliteSort::Status,
+            _ => return None,
+        };
+        let order = match parts.next()? {
+            "ASC" => SortOrder::Asc,
+            "DESC" => SortOrder::Desc,
+            _ => return None,
+        };
+        let id = parts.next()?.parse().ok()?;
+        let key = parts.next()?.to_string();
+
+        // The key is cast in the query, so it has to be rejected here if it can not be parsed
+        let valid_key = match sort {
+            EliteSort::Id | EliteSort::Status => key.parse::<i32>().is_ok(),
+            EliteSort::Ign => true,
+            EliteSort::Birthday => key == "infinity" || key.parse::<NaiveDate>().is_ok(),
+        };
+
+        valid_key.then_some(Self { sort, order, key, id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = EliteRosterCursor {
+            sort: EliteSort::Ign,
+            order: SortOrder::Desc,
+            key: String::from("some|ign"),
+            id: 7,
+        };
+
+        assert_eq!(EliteRosterCursor::decode(&cursor.encode()), Some(cursor));
+    }
+
+    #[test]
+    fn cursor_rejects_key_not_matching_sort() {
+        let cursor = EliteRosterCursor {
+            sort: EliteSort::Birthday,
+            order: SortOrder::Asc,
+            key: String::from("not a date"),
+            id: 7,
+        };
+
+        assert_eq!(EliteRosterCursor::decode(&cursor.encode()), None);
+    }
+
+    #[test]
+    fn cursor_accepts_infinity_birthday() {
+        let cursor = EliteRosterCursor {
+            sort: EliteSort::Birthday,
+            order: SortOrder::Asc,
+            key: String::from("infinity"),
+            id: 7,
+        };
+
+        assert_eq!(EliteRosterCursor::decode(&cursor.encode()), Some(cursor));
+    }
+}
@@ -1,5 +1,16 @@
 #![allow(clippy::module_inception)]
 
+mod birthday;
 mod elite;
+mod elite_change;
+mod elite_export;
+mod elite_import;
+mod elite_roster;
+pub mod validation;
 
+pub use birthday::*;
 pub use elite::*;
+pub use elite_change::*;
+pub use elite_export::*;
+pub use elite_import::*;
+pub use elite_roster::*;
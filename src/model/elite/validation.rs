@@ -0,0 +1,51 @@
+use chrono::{NaiveDate, Utc};
+use std::collections::BTreeMap;
+
+/// Validation messages keyed by the name of the invalid field
+pub type FieldErrors = BTreeMap<String, String>;
+
+/// Officially assigned ISO 3166-1 alpha-2 country codes
+const ISO_3166_ALPHA_2: [&str; 249] = [
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ", "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI",
+    "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS", "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE", "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK",
+    "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF", "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM", "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN",
+    "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC", "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA", "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP",
+    "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG", "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS", "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF",
+    "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO", "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// Earliest birthday that is still considered plausible
+const MIN_BIRTHDAY: NaiveDate = NaiveDate::from_ymd_opt(1900, 1, 1).unwrap();
+
+/// Discord snowflakes are 64 bit integers, which are 17 to 20 digits long for any id issued so far
+pub fn validate_discord_snowflake(value: &str) -> Result<(), String> {
+    if (17..=20).contains(&value.len()) && value.bytes().all(|b| b.is_ascii_digit()) && value.parse::<u64>().is_ok() {
+        Ok(())
+    } else {
+        Err("must be a discord snowflake".to_string())
+    }
+}
+
+/// Returns the country code in its canonical upper case form
+pub fn parse_country_code(value: &str) -> Result<String, String> {
+    let country_code = value.to_ascii_uppercase();
+
+    if ISO_3166_ALPHA_2.contains(&country_code.as_str()) {
+        Ok(country_code)
+    } else {
+        Err("must be an ISO 3166-1 alpha-2 country code".to_string())
+    }
+}
+
+pub fn validate_birthday(birthday: &NaiveDate) -> Result<(), String> {
+    if *birthday < MIN_BIRTHDAY || *birthday > Utc::now().date_naive() {
+        return Err(format!("must be between {MIN_BIRTHDAY} and today"));
+    }
+
+    Ok(())
+}
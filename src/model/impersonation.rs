@@ -0,0 +1,68 @@
+use crate::model::elite::validation::FieldErrors;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+use tokio_postgres::Row;
+
+/// Minutes an impersonation lasts if no duration is given
+pub const DEFAULT_IMPERSONATION_MINUTES: i64 = 15;
+pub const MAX_IMPERSONATION_MINUTES: i64 = 60;
+
+#[derive(EnumString, Display, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImpersonationAction {
+    #[strum(serialize = "start")]
+    Start,
+    /// A request made while impersonating, including blocked mutating requests
+    #[strum(serialize = "request")]
+    Request,
+    #[strum(serialize = "stop")]
+    Stop,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ImpersonationEvent {
+    pub id: i32,
+    pub impersonator_id: String,
+    pub target_user_id: String,
+    pub action: String,
+    pub method: Option<String>,
+    pub path: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<&Row> for ImpersonationEvent {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            impersonator_id: row.get("impersonator_id"),
+            target_user_id: row.get("target_user_id"),
+            action: row.get("action"),
+            method: row.get("method"),
+            path: row.get("path"),
+            timestamp: row.get("timestamp"),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ImpersonationForCreate {
+    pub discord_user_id: String,
+    pub minutes: Option<i64>,
+}
+
+impl ImpersonationForCreate {
+    pub fn validate(&mut self) -> Result<(), FieldErrors> {
+        let mut errors = FieldErrors::new();
+
+        self.discord_user_id = self.discord_user_id.trim().to_string();
+        if self.discord_user_id.is_empty() {
+            errors.insert("discord_user_id".to_string(), "is required".to_string());
+        }
+        if self.minutes.is_some_and(|minutes| !(1..=MAX_IMPERSONATION_MINUTES).contains(&minutes)) {
+            errors.insert("minutes".to_string(), format!("must be between 1 and {MAX_IMPERSONATION_MINUTES}"));
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MinecraftServer {
+    pub id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// Last time the server pulled an export
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MinecraftServerForCreate {
+    pub name: String,
+}
+
+impl From<&Row> for MinecraftServer {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            name: row.get("name"),
+            created_at: row.get("created_at"),
+            last_used_at: row.get("last_used_at"),
+        }
+    }
+}
+
+/// Entry of a vanilla `whitelist.json`
+#[derive(Serialize, Debug)]
+pub struct WhitelistEntry {
+    pub uuid: Uuid,
+    pub name: String,
+}
+
+/// Entry of a vanilla `ops.json`
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsEntry {
+    pub uuid: Uuid,
+    pub name: String,
+    pub level: u8,
+    pub bypasses_player_limit: bool,
+}
+
+/// Primary group a player should have in LuckPerms
+#[derive(Serialize, Debug)]
+pub struct GroupAssignment {
+    pub uuid: Uuid,
+    pub name: String,
+    pub group: String,
+}
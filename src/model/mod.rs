@@ -1,4 +1,13 @@
+pub mod api_key;
+pub mod auth_event;
 pub mod discord;
 pub mod elite;
+pub mod impersonation;
+pub mod minecraft;
+pub mod mojang;
+pub mod name_history;
+pub mod permission;
 pub mod recent_change;
 pub mod session;
+pub mod skin_history;
+pub mod tracked_uuid;
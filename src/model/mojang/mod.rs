@@ -0,0 +1,5 @@
+mod profile;
+mod textures;
+
+pub use profile::{Profile, is_valid_ign};
+pub use textures::TexturesPayload;
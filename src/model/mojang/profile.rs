@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Represents a profile as returned by the session server.
+/// Reference: https://minecraft.wiki/w/Mojang_API#Query_player's_skin_and_cape
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Profile {
+    /// The player's uuid (returned without dashes)
+    pub id: Uuid,
+
+    /// The player's current name
+    pub name: String,
+
+    /// Signed profile properties, currently only `textures`
+    #[serde(default)]
+    pub properties: Vec<ProfileProperty>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfileProperty {
+    pub name: String,
+    /// Base64 encoded property value
+    pub value: String,
+    pub signature: Option<String>,
+}
+
+/// Checks whether a name could be a Minecraft ign (up to 16 alphanumeric or underscore characters).
+/// Some legacy accounts have names shorter than the current 3 character minimum, so those are accepted too.
+pub fn is_valid_ign(name: &str) -> bool {
+    (1..=16).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Decoded value of the `textures` profile property.
+/// Reference: https://minecraft.wiki/w/Mojang_API#Query_player's_skin_and_cape
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TexturesPayload {
+    pub timestamp: i64,
+    pub profile_id: Uuid,
+    pub profile_name: String,
+    pub textures: Textures,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub struct Textures {
+    /// Missing if the player uses one of the default skins
+    pub skin: Option<Texture>,
+    pub cape: Option<Texture>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Texture {
+    /// e.g. `http://textures.minecraft.net/texture/<texture_id>`
+    pub url: String,
+}
+
+impl Texture {
+    /// The texture hash, which is the last path segment of the texture url
+    pub fn texture_id(&self) -> Option<&str> {
+        self.url.rsplit('/').next().filter(|id| !id.is_empty())
+    }
+}
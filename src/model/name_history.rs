@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+#[derive(Serialize, Debug)]
+pub struct NameHistoryEntry {
+    pub id: i32,
+    pub uuid: Uuid,
+    pub ign: String,
+    pub timestamp: DateTime<Utc>,
+    pub source: String,
+}
+
+impl From<&Row> for NameHistoryEntry {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            uuid: row.get::<_, Uuid>("uuid"),
+            ign: row.get("ign"),
+            timestamp: row.get("timestamp"),
+            source: row.get("source"),
+        }
+    }
+}
+
+/// A period in which a player used the same ign. `to` is `None` for the current ign.
+#[derive(Serialize, Debug)]
+pub struct NamePeriod {
+    pub ign: String,
+    pub from: DateTime<Utc>,
+    pub to: Option<DateTime<Utc>>,
+    /// Source of the entry that started this period, either `task` or `manual`
+    pub source: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PlayerNameHistory {
+    pub uuid: Uuid,
+    pub current_ign: Option<String>,
+    pub names: Vec<NamePeriod>,
+}
+
+impl PlayerNameHistory {
+    /// Collapses consecutive entries with the same ign into periods.
+    /// `entries` must belong to `uuid` and be ordered by timestamp ascending.
+    pub fn from_entries(uuid: Uuid, entries: Vec<NameHistoryEntry>) -> Self {
+        let mut names: Vec<NamePeriod> = Vec::new();
+
+        for entry in entries {
+            if names.last().is_some_and(|period| period.ign == entry.ign) {
+                continue;
+            }
+
+            if let Some(period) = names.last_mut() {
+                period.to = Some(entry.timestamp);
+            }
+
+            names.push(NamePeriod {
+                ign: entry.ign,
+                from: entry.timestamp,
+                to: None,
+                source: entry.source,
+            });
+        }
+
+        Self {
+            uuid,
+            current_ign: names.last().map(|period| period.ign.clone()),
+            names,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i32, ign: &str, timestamp: i64) -> NameHistoryEntry {
+        NameHistoryEntry {
+            id,
+            uuid: Uuid::nil(),
+            ign: ign.to_string(),
+            timestamp: DateTime::from_timestamp(timestamp, 0).unwrap(),
+            source: String::from("task"),
+        }
+    }
+
+    #[test]
+    fn collapses_repeated_igns_into_periods() {
+        let history = PlayerNameHistory::from_entries(
+            Uuid::nil(),
+            vec![entry(1, "a", 10), entry(2, "a", 20), entry(3, "b", 30), entry(4, "a", 40)],
+        );
+
+        let periods: Vec<_> = history
+            .names
+            .iter()
+            .map(|period| (period.ign.as_str(), period.from.timestamp(), period.to.map(|to| to.timestamp())))
+            .collect();
+        assert_eq!(periods, vec![("a", 10, Some(30)), ("b", 30, Some(40)), ("a", 40, None)]);
+        assert_eq!(history.current_ign.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn empty_history_has_no_current_ign() {
+        let history = PlayerNameHistory::from_entries(Uuid::nil(), Vec::new());
+
+        assert!(history.names.is_empty());
+        assert_eq!(history.current_ign, None);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::model::elite::validation::FieldErrors;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter, EnumString};
+use tokio_postgres::Row;
+
+/// Actions beyond viewing the roster, granted to discord roles through [`RoleMapping`]s
+#[derive(EnumString, Display, EnumIter, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Permission {
+    /// Create and edit elites, including csv imports
+    #[strum(serialize = "roster:edit")]
+    #[serde(rename = "roster:edit")]
+    RosterEdit,
+    #[strum(serialize = "roster:export")]
+    #[serde(rename = "roster:export")]
+    RosterExport,
+    #[strum(serialize = "roster:read-ex-elites")]
+    #[serde(rename = "roster:read-ex-elites")]
+    RosterReadExElites,
+    /// Tracked uuids as well as their ign and skin history
+    #[strum(serialize = "tracker:manage")]
+    #[serde(rename = "tracker:manage")]
+    TrackerManage,
+    #[strum(serialize = "audit:read")]
+    #[serde(rename = "audit:read")]
+    AuditRead,
+    /// Revoke the sessions of other users
+    #[strum(serialize = "sessions:manage")]
+    #[serde(rename = "sessions:manage")]
+    SessionsManage,
+    #[strum(serialize = "api-keys:manage")]
+    #[serde(rename = "api-keys:manage")]
+    ApiKeysManage,
+    #[strum(serialize = "minecraft:manage")]
+    #[serde(rename = "minecraft:manage")]
+    MinecraftManage,
+    /// Edit the role mappings themselves
+    #[strum(serialize = "roles:manage")]
+    #[serde(rename = "roles:manage")]
+    RolesManage,
+    /// View the dashboard as another user
+    #[strum(serialize = "users:impersonate")]
+    #[serde(rename = "users:impersonate")]
+    UsersImpersonate,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RoleMapping {
+    pub discord_role_id: String,
+    /// Name of the permission set, e.g. the name of the discord role
+    pub name: String,
+    pub permissions: Vec<Permission>,
+    /// Discord id of the admin that last changed the mapping
+    pub updated_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RoleMappingForUpdate {
+    pub name: String,
+    pub permissions: Vec<Permission>,
+}
+
+impl RoleMappingForUpdate {
+    pub fn validate(&mut self) -> Result<(), FieldErrors> {
+        let mut errors = FieldErrors::new();
+
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() || self.name.len() > 64 {
+            errors.insert("name".to_string(), "must be between 1 and 64 characters".to_string());
+        }
+        if self.permissions.is_empty() {
+            errors.insert("permissions".to_string(), "must contain at least one permission".to_string());
+        }
+
+        self.permissions.sort_by_key(|permission| permission.to_string());
+        self.permissions.dedup();
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+impl From<&Row> for RoleMapping {
+    fn from(row: &Row) -> Self {
+        Self {
+            discord_role_id: row.get("discord_role_id"),
+            name: row.get("name"),
+            permissions: row.get::<_, Vec<String>>("permissions").iter().filter_map(|permission| permission.parse().ok()).collect(),
+            updated_by: row.get("updated_by"),
+            updated_at: row.get("updated_at"),
+        }
+    }
+}
@@ -1,13 +1,24 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use tokio_postgres::Row;
 use uuid::Uuid;
 
+pub const RECENT_CHANGE_EVENT_TYPES: [&str; 4] = ["IGN_CHANGE", "SKIN_CHANGE", "add", "remove"];
+
 #[derive(Serialize, Debug)]
 pub struct RecentChange {
+    /// Id of the row in the history table the event originates from, only unique per event type
+    #[serde(skip)]
+    pub id: i32,
     pub uuid: Uuid,
-    pub old_ign: String,
-    pub new_ign: String,
+    pub old_ign: Option<String>,
+    pub new_ign: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_texture_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_texture_id: Option<String>,
     pub timestamp: DateTime<Utc>,
     pub event_type: String,
 }
@@ -15,11 +26,90 @@ pub struct RecentChange {
 impl From<Row> for RecentChange {
     fn from(row: Row) -> Self {
         Self {
+            id: row.get("id"),
             uuid: row.get::<_, Uuid>("uuid"),
             old_ign: row.get("old_ign"),
             new_ign: row.get("new_ign"),
+            old_texture_id: row.get("old_texture_id"),
+            new_texture_id: row.get("new_texture_id"),
             timestamp: row.get("timestamp"),
             event_type: row.get("event_type"),
         }
     }
 }
+
+#[derive(Debug, Default)]
+pub struct RecentChangesFilter {
+    pub event_types: Option<Vec<String>>,
+    pub uuid: Option<Uuid>,
+    /// Inclusive lower bound
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive upper bound
+    pub until: Option<DateTime<Utc>>,
+    /// Only return changes older than the cursor
+    pub cursor: Option<RecentChangesCursor>,
+}
+
+/// Position in the recent changes feed, ordered by `(timestamp, event_type, id)` descending.
+/// `event_type` is part of the key because ids are only unique within their history table.
+#[derive(Debug, PartialEq)]
+pub struct RecentChangesCursor {
+    pub timestamp: DateTime<Utc>,
+    pub event_type: String,
+    pub id: i32,
+}
+
+impl RecentChangesCursor {
+    pub fn after(change: &RecentChange) -> Self {
+        Self {
+            timestamp: change.timestamp,
+            event_type: change.event_type.clone(),
+            id: change.id,
+        }
+    }
+
+    /// Encodes the cursor into an opaque url safe string
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}|{}", self.timestamp.timestamp_micros(), self.event_type, self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let decoded = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+        let mut parts = decoded.splitn(3, '|');
+
+        let timestamp = DateTime::from_timestamp_micros(parts.next()?.parse().ok()?)?;
+        let event_type = parts.next()?.to_string();
+        let id = parts.next()?.parse().ok()?;
+
+        RECENT_CHANGE_EVENT_TYPES.contains(&event_type.as_str()).then_some(Self { timestamp, event_type, id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = RecentChangesCursor {
+            timestamp: DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap(),
+            event_type: RECENT_CHANGE_EVENT_TYPES[0].to_string(),
+            id: 42,
+        };
+
+        assert_eq!(RecentChangesCursor::decode(&cursor.encode()), Some(cursor));
+    }
+
+    #[test]
+    fn cursor_rejects_unknown_event_type() {
+        let cursor = URL_SAFE_NO_PAD.encode("1700000000123456|unknown|42");
+
+        assert_eq!(RecentChangesCursor::decode(&cursor), None);
+    }
+
+    #[test]
+    fn cursor_rejects_garbage() {
+        assert_eq!(RecentChangesCursor::decode("not a cursor"), None);
+        assert_eq!(RecentChangesCursor::decode(&URL_SAFE_NO_PAD.encode("1700000000123456")), None);
+    }
+}
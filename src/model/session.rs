@@ -1,24 +1,136 @@
-use crate::app::constants::{DISCORD_ACCESS_TOKEN_KEY, DISCORD_REFRESH_TOKEN_KEY, USER_ID_KEY, USER_ROLE_KEY};
+use crate::app::constants::{
+    CREATED_AT_KEY, DISCORD_ACCESS_TOKEN_KEY, DISCORD_REFRESH_TOKEN_KEY, IMPERSONATION_EXPIRES_AT_KEY, IMPERSONATION_PERMISSIONS_KEY,
+    IMPERSONATION_ROLE_KEY, IMPERSONATION_USER_ID_KEY, IP_KEY, LAST_SEEN_AT_KEY, PERMISSIONS_KEY, ROLE_CHECK_RETRY_AT_KEY, ROLE_CHECKED_AT_KEY,
+    USER_AGENT_KEY, USER_ID_KEY, USER_ROLE_KEY,
+};
+use crate::model::api_key::{ApiKey, ApiKeyScope};
+use crate::model::permission::Permission;
+use chrono::{DateTime, Utc};
 use redis::{FromRedisValue, RedisError, RedisResult, Value};
 use serde::Serialize;
 use std::collections::HashMap;
+use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumString};
 
 #[derive(Debug, Serialize, Clone)]
 pub struct Session {
     pub user: SessionUser,
-    pub discord: DiscordTokens,
+    /// `None` for api key sessions
+    pub discord: Option<DiscordTokens>,
+    /// Scopes of an api key session, `None` for user sessions which are only limited by their role
+    pub scopes: Option<Vec<ApiKeyScope>>,
+    /// Last time the role was checked against the guild, `None` for api key sessions and sessions from before role checks
+    pub role_checked_at: Option<DateTime<Utc>>,
+    /// Set when discord could not be reached for the role check, the check is not retried before
+    pub role_check_retry_at: Option<DateTime<Utc>>,
+    /// Login of the device, `None` for api key sessions
+    pub device: Option<SessionDevice>,
+    /// "View as" overlay a staff member put on their own session, see [`Session::apply_impersonation`]
+    pub impersonation: Option<Impersonation>,
+    /// The staff member behind the session while the overlay is applied
+    pub impersonator: Option<Impersonator>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Impersonation {
+    pub user: SessionUser,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Impersonator {
+    pub id: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionDevice {
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// A session of a user as shown in their session list
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    /// Public id of the session, the session id itself is a secret only known to the cookie
+    pub id: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// Whether this is the session of the request
+    pub current: bool,
+}
+
+impl SessionInfo {
+    pub fn new(id: String, device: SessionDevice, current: bool) -> Self {
+        Self {
+            id,
+            created_at: device.created_at,
+            last_seen_at: device.last_seen_at,
+            ip: device.ip,
+            user_agent: device.user_agent,
+            current,
+        }
+    }
+}
+
+impl Session {
+    /// Builds a session with the bot role for a request authenticated by an api key
+    pub fn for_api_key(api_key: &ApiKey) -> Self {
+        Self {
+            user: SessionUser {
+                id: api_key.actor_id(),
+                role: UserRole::Bot,
+                permissions: api_key_permissions(&api_key.scopes),
+            },
+            discord: None,
+            scopes: Some(api_key.scopes.clone()),
+            role_checked_at: None,
+            role_check_retry_at: None,
+            device: None,
+            impersonation: None,
+            impersonator: None,
+        }
+    }
+
+    pub fn is_api_key(&self) -> bool {
+        self.scopes.is_some()
+    }
+
+    /// Runs the session as the impersonated user while the overlay has not expired, the staff member is kept as impersonator.
+    /// The impersonated user only keeps the permissions the staff member holds as well.
+    pub fn apply_impersonation(&mut self) {
+        let Some(mut impersonation) = self.impersonation.take() else {
+            return;
+        };
+        if impersonation.expires_at <= Utc::now() {
+            return;
+        }
+
+        impersonation.user.permissions.retain(|permission| self.user.has_permission(*permission));
+
+        let staff = std::mem::replace(&mut self.user, impersonation.user);
+        self.impersonator = Some(Impersonator {
+            id: staff.id,
+            expires_at: impersonation.expires_at,
+        });
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct SessionUser {
     pub id: String,
     pub role: UserRole,
+    /// Resolved from the discord roles of the user on login and on every role check
+    pub permissions: Vec<Permission>,
 }
 
 impl SessionUser {
-    pub fn is_staff(&self) -> bool {
-        matches!(self.role, UserRole::Staff)
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
     }
 }
 
@@ -28,7 +140,7 @@ pub struct DiscordTokens {
     pub refresh_token: String,
 }
 
-#[derive(EnumString, Display, Debug, Serialize, Clone)]
+#[derive(EnumString, Display, Debug, Serialize, Clone, PartialEq)]
 pub enum UserRole {
     #[strum(serialize = "staff")]
     Staff,
@@ -50,6 +162,13 @@ impl FromRedisValue for Session {
             .parse::<UserRole>()
             .map_err(|e| RedisError::from((redis::ErrorKind::TypeError, "Invalid role", format!("Invalid role: {e}"))))?;
 
+        // Sessions from before permissions were stored fall back to what their role could do
+        let permissions = match map.get(PERMISSIONS_KEY) {
+            Some(permissions) => parse_permissions(permissions),
+            None if user_role == UserRole::Staff => Permission::iter().collect(),
+            None => Vec::new(),
+        };
+
         let refresh_token = map
             .get(DISCORD_REFRESH_TOKEN_KEY)
             .ok_or_else(|| RedisError::from((redis::ErrorKind::TypeError, "Missing refresh_token")))?
@@ -57,12 +176,66 @@ impl FromRedisValue for Session {
 
         let access_token = map.get(DISCORD_ACCESS_TOKEN_KEY).cloned();
 
+        let timestamp = |key: &str| {
+            map.get(key)
+                .and_then(|timestamp| timestamp.parse::<i64>().ok())
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        };
+
+        let role_checked_at = timestamp(ROLE_CHECKED_AT_KEY);
+        let role_check_retry_at = timestamp(ROLE_CHECK_RETRY_AT_KEY);
+
+        let device = SessionDevice {
+            created_at: timestamp(CREATED_AT_KEY),
+            last_seen_at: timestamp(LAST_SEEN_AT_KEY),
+            ip: map.get(IP_KEY).cloned(),
+            user_agent: map.get(USER_AGENT_KEY).cloned(),
+        };
+
+        let impersonation = match (
+            map.get(IMPERSONATION_USER_ID_KEY),
+            map.get(IMPERSONATION_ROLE_KEY).and_then(|role| role.parse::<UserRole>().ok()),
+            timestamp(IMPERSONATION_EXPIRES_AT_KEY),
+        ) {
+            (Some(user_id), Some(role), Some(expires_at)) => Some(Impersonation {
+                user: SessionUser {
+                    id: user_id.clone(),
+                    role,
+                    permissions: map.get(IMPERSONATION_PERMISSIONS_KEY).map(|permissions| parse_permissions(permissions)).unwrap_or_default(),
+                },
+                expires_at,
+            }),
+            _ => None,
+        };
+
         Ok(Self {
             user: SessionUser {
                 id: user_id,
                 role: user_role,
+                permissions,
             },
-            discord: DiscordTokens { access_token, refresh_token },
+            discord: Some(DiscordTokens { access_token, refresh_token }),
+            scopes: None,
+            role_checked_at,
+            role_check_retry_at,
+            device: Some(device),
+            impersonation,
+            impersonator: None,
         })
     }
 }
+
+fn parse_permissions(permissions: &str) -> Vec<Permission> {
+    permissions.split(',').filter_map(|permission| permission.parse().ok()).collect()
+}
+
+fn api_key_permissions(scopes: &[ApiKeyScope]) -> Vec<Permission> {
+    let mut permissions: Vec<Permission> = Vec::new();
+    for permission in scopes.iter().flat_map(ApiKeyScope::permissions) {
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+
+    permissions
+}
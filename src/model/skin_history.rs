@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+#[derive(Serialize, Debug)]
+pub struct SkinHistoryEntry {
+    pub id: i32,
+    pub uuid: Uuid,
+    pub texture_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub source: String,
+}
+
+impl From<&Row> for SkinHistoryEntry {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            uuid: row.get::<_, Uuid>("uuid"),
+            texture_id: row.get("texture_id"),
+            timestamp: row.get("timestamp"),
+            source: row.get("source"),
+        }
+    }
+}
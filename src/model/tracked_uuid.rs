@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+#[derive(Serialize, Debug)]
+pub struct TrackedUuid {
+    pub id: i32,
+    pub uuid: Uuid,
+    pub last_checked: DateTime<Utc>,
+    pub ign: Option<String>,
+}
+
+/// Either `uuid` or `ign` has to be provided
+#[derive(Deserialize, Debug)]
+pub struct TrackedUuidForCreate {
+    pub uuid: Option<Uuid>,
+    pub ign: Option<String>,
+}
+
+impl From<&Row> for TrackedUuid {
+    fn from(row: &Row) -> Self {
+        Self {
+            id: row.get("id"),
+            uuid: row.get::<_, Uuid>("uuid"),
+            last_checked: row.get("last_checked"),
+            ign: row.get("ign"),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::app::error::AppError;
+use crate::db::error::DbError;
+use crate::model::api_key::{ApiKey, ApiKeyForCreate};
+use crate::service::error::ServiceError::{CreatePreparedStatementError, DbConnectionError};
+use crate::service::token::{generate_token, hash_token};
+use crate::web::error::Error::ApiKeyNotFound;
+use deadpool_postgres::Pool;
+
+#[derive(Clone)]
+pub struct ApiKeyService {
+    db_pool: Pool,
+}
+
+impl ApiKeyService {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl ApiKeyService {
+    pub async fn keys_all(&self) -> Result<Vec<ApiKey>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM api_keys ORDER BY id")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let keys = con.query(&stmt, &[]).await.map_err(|e| DbError::QueryError(e.to_string()))?.iter().map(ApiKey::from).collect();
+
+        Ok(keys)
+    }
+
+    /// Creates a key and returns it together with the secret, the secret can not be retrieved again
+    pub async fn create_key(&self, new_key: &ApiKeyForCreate, created_by: &str) -> Result<(ApiKey, String), AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let secret = generate_token();
+        let scopes: Vec<String> = new_key.scopes.iter().map(ToString::to_string).collect();
+
+        let stmt = con
+            .prepare_cached("INSERT INTO api_keys (name, key_hash, scopes, created_by, expires_at) VALUES ($1, $2, $3, $4, $5) RETURNING *")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let key = con
+            .query_one(&stmt, &[&new_key.name, &hash_token(&secret), &scopes, &created_by, &new_key.expires_at])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok((ApiKey::from(&key), secret))
+    }
+
+    /// Revokes a key, revoked keys are kept so their audit entries can still be attributed
+    pub async fn revoke_key(&self, key_id: i32) -> Result<(), AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("UPDATE api_keys SET revoked_at = now() WHERE id = $1 AND revoked_at IS NULL")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let revoked = con.execute(&stmt, &[&key_id]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+        if revoked == 0 {
+            return Err(ApiKeyNotFound(format!("No active api key with id {key_id} exists.")).into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the active key matching a secret and marks it as used, together with whether this use should be recorded as an auth event.
+    /// Only the first use of a key per hour is recorded, also across instances of the api.
+    pub async fn authenticate(&self, secret: &str) -> Result<Option<(ApiKey, bool)>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached(
+                "
+                UPDATE api_keys
+                SET last_used_at = now(),
+                    use_recorded_at = CASE
+                        WHEN use_recorded_at IS NULL OR use_recorded_at <= now() - interval '1 hour' THEN now()
+                        ELSE use_recorded_at
+                    END
+                WHERE key_hash = $1 AND revoked_at IS NULL AND (expires_at IS NULL OR expires_at > now())
+                RETURNING *, use_recorded_at = now() AS record_use
+                ",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let key = con
+            .query_opt(&stmt, &[&hash_token(secret)])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .map(|row| (ApiKey::from(&row), row.get("record_use")));
+
+        Ok(key)
+    }
+}
@@ -0,0 +1,90 @@
+use crate::app::error::AppError;
+use crate::db::error::DbError;
+use crate::model::auth_event::{AuthEvent, AuthEventFilter, AuthEventForCreate, RequestOrigin};
+use crate::service::error::ServiceError::{CreatePreparedStatementError, DbConnectionError};
+use deadpool_postgres::Pool;
+use tracing::warn;
+
+#[derive(Clone)]
+pub struct AuthEventService {
+    db_pool: Pool,
+}
+
+impl AuthEventService {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl AuthEventService {
+    /// Stores an event in the audit log. A failing insert is only logged, so logins and logouts keep working while the database is unavailable.
+    pub async fn record(&self, event: AuthEventForCreate, origin: &RequestOrigin) {
+        if let Err(e) = self.insert(&event, origin).await {
+            warn!("{:<12} - Failed to record auth event {:?}: {:?}", "AUTH_EVENT", event, e);
+        }
+    }
+
+    async fn insert(&self, event: &AuthEventForCreate, origin: &RequestOrigin) -> Result<(), AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached(
+                "
+                INSERT INTO auth_events (event, discord_user_id, actor_id, reason, details, ip, user_agent, request_id, timestamp)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now())
+                ",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        con.execute(
+            &stmt,
+            &[
+                &event.event.to_string(),
+                &event.discord_user_id,
+                &event.actor_id,
+                &event.reason,
+                &event.details,
+                &origin.ip,
+                &origin.user_agent,
+                &origin.request_id,
+            ],
+        )
+        .await
+        .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Newest events first
+    pub async fn events(&self, filter: &AuthEventFilter, limit: i64, offset: i64) -> Result<Vec<AuthEvent>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached(
+                "
+                SELECT * FROM auth_events
+                WHERE ($1::TEXT IS NULL OR event = $1)
+                  AND ($2::TEXT IS NULL OR discord_user_id = $2 OR actor_id = $2)
+                  AND ($3::TIMESTAMPTZ IS NULL OR timestamp >= $3)
+                  AND ($4::TIMESTAMPTZ IS NULL OR timestamp < $4)
+                ORDER BY timestamp DESC, id DESC
+                LIMIT $5 OFFSET $6
+                ",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let event = filter.event.map(|event| event.to_string());
+
+        let events = con
+            .query(&stmt, &[&event, &filter.discord_user_id, &filter.since, &filter.until, &limit, &offset])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .iter()
+            .map(AuthEvent::from)
+            .collect();
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::app::config::TrackerConfig;
+use crate::app::constants::{AVATAR_KEY_PREFIX, ONE_MONTH};
+use crate::app::error::AppError;
+use crate::service::error::ServiceError::{InvalidSkinTexture, TextureRequestError};
+use crate::web::error::Error;
+use image::imageops::{self, FilterType};
+use image::{ImageFormat, RgbaImage};
+use redis::AsyncCommands;
+use redis::aio::ConnectionManager;
+use reqwest::Client;
+use std::io::Cursor;
+use std::sync::Arc;
+use tracing::debug;
+
+#[derive(Clone)]
+pub struct AvatarService {
+    redis: Arc<ConnectionManager>,
+    client: Client,
+    textures_url: String,
+}
+
+impl AvatarService {
+    pub fn new(config: &TrackerConfig, redis: ConnectionManager) -> Self {
+        Self {
+            redis: Arc::new(redis),
+            client: Client::new(),
+            textures_url: config.textures_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl AvatarService {
+    /// Returns the rendered head of a skin as png bytes.
+    /// Renders are cached by texture id, so a head is only rendered again once the skin changes.
+    pub async fn get_avatar(&self, texture_id: &str, size: u32) -> Result<Vec<u8>, AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let avatar_key = format!("{}:{}:{}", AVATAR_KEY_PREFIX, texture_id, size);
+
+        let cached = con.get::<_, Option<Vec<u8>>>(&avatar_key).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+        if let Some(avatar) = cached {
+            return Ok(avatar);
+        }
+
+        debug!("{:<12} - Rendering avatar {} ({}px)", "AVATAR", texture_id, size);
+
+        let skin = self.fetch_skin(texture_id).await?;
+        let avatar = render_head(&skin, size)?;
+
+        let _: () = con.set_ex(&avatar_key, &avatar, ONE_MONTH as u64).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        Ok(avatar)
+    }
+
+    async fn fetch_skin(&self, texture_id: &str) -> Result<Vec<u8>, AppError> {
+        let url = format!("{}/texture/{}", self.textures_url, texture_id);
+
+        let response = self.client.get(url).send().await.map_err(|e| TextureRequestError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(TextureRequestError(response.status().to_string()).into());
+        }
+
+        let skin = response.bytes().await.map_err(|e| TextureRequestError(e.to_string()))?;
+
+        Ok(skin.to_vec())
+    }
+}
+
+/// Crops the face and the hat overlay out of a skin, composites them and scales the result with nearest-neighbour
+fn render_head(skin: &[u8], size: u32) -> Result<Vec<u8>, AppError> {
+    let skin = image::load_from_memory_with_format(skin, ImageFormat::Png).map_err(|e| InvalidSkinTexture(e.to_string()))?.to_rgba8();
+
+    // Skins are 64x64 (or legacy 64x32), HD skins are multiples of that
+    if skin.width() < 64 || skin.width() % 64 != 0 || (skin.height() != skin.width() && skin.height() * 2 != skin.width()) {
+        return Err(InvalidSkinTexture(format!("unexpected skin dimensions {}x{}", skin.width(), skin.height())).into());
+    }
+    let scale = skin.width() / 64;
+
+    let mut head: RgbaImage = imageops::crop_imm(&skin, 8 * scale, 8 * scale, 8 * scale, 8 * scale).to_image();
+    let hat = imageops::crop_imm(&skin, 40 * scale, 8 * scale, 8 * scale, 8 * scale).to_image();
+    imageops::overlay(&mut head, &hat, 0, 0);
+
+    let head = imageops::resize(&head, size, size, FilterType::Nearest);
+
+    let mut png = Vec::new();
+    head.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).map_err(|e| InvalidSkinTexture(e.to_string()))?;
+
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const FACE: Rgba<u8> = Rgba([200, 0, 0, 255]);
+    const HAT: Rgba<u8> = Rgba([0, 0, 200, 255]);
+
+    /// A skin with a red face and a hat overlay that only covers the top left pixel of the face
+    fn skin_png(width: u32, height: u32) -> Vec<u8> {
+        let scale = width / 64;
+        let mut skin = RgbaImage::new(width, height);
+        for x in 8 * scale..16 * scale {
+            for y in 8 * scale..16 * scale {
+                skin.put_pixel(x, y, FACE);
+            }
+        }
+        for x in 40 * scale..41 * scale {
+            for y in 8 * scale..9 * scale {
+                skin.put_pixel(x, y, HAT);
+            }
+        }
+
+        let mut png = Vec::new();
+        skin.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).unwrap();
+        png
+    }
+
+    fn render(skin: &[u8], size: u32) -> RgbaImage {
+        let png = render_head(skin, size).unwrap();
+        image::load_from_memory_with_format(&png, ImageFormat::Png).unwrap().to_rgba8()
+    }
+
+    #[test]
+    fn renders_face_with_hat_overlay() {
+        let head = render(&skin_png(64, 64), 64);
+
+        assert_eq!(head.dimensions(), (64, 64));
+        // Every skin pixel becomes an 8x8 block
+        assert_eq!(*head.get_pixel(7, 7), HAT);
+        assert_eq!(*head.get_pixel(8, 0), FACE);
+        assert_eq!(*head.get_pixel(63, 63), FACE);
+    }
+
+    #[test]
+    fn renders_legacy_and_hd_skins() {
+        assert_eq!(*render(&skin_png(64, 32), 16).get_pixel(15, 15), FACE);
+        assert_eq!(*render(&skin_png(128, 128), 16).get_pixel(0, 0), HAT);
+    }
+
+    #[test]
+    fn rejects_unexpected_dimensions() {
+        assert!(render_head(&skin_png(64, 48), 16).is_err());
+        assert!(render_head(b"not a png", 16).is_err());
+    }
+}
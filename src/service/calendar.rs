@@ -0,0 +1,58 @@
+use crate::app::error::AppError;
+use crate::db::error::DbError;
+use crate::service::error::ServiceError::{CreatePreparedStatementError, DbConnectionError};
+use crate::service::token::{generate_token, hash_token};
+use deadpool_postgres::Pool;
+
+#[derive(Clone)]
+pub struct CalendarService {
+    db_pool: Pool,
+}
+
+impl CalendarService {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl CalendarService {
+    /// Creates a new feed token for a user, replacing any previous one. The token is only returned once.
+    pub async fn rotate_token(&self, discord_user_id: &str) -> Result<String, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let token = generate_token();
+
+        let stmt = con
+            .prepare_cached(
+                "
+                INSERT INTO calendar_tokens (discord_user_id, token_hash, created_at)
+                VALUES ($1, $2, now())
+                ON CONFLICT (discord_user_id) DO UPDATE SET token_hash = EXCLUDED.token_hash, created_at = EXCLUDED.created_at
+                ",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        con.execute(&stmt, &[&discord_user_id, &hash_token(&token)]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(token)
+    }
+
+    /// Returns the discord id of the user owning a feed token
+    pub async fn find_user_by_token(&self, token: &str) -> Result<Option<String>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT discord_user_id FROM calendar_tokens WHERE token_hash = $1")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let discord_user_id = con
+            .query_opt(&stmt, &[&hash_token(token)])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .map(|row| row.get("discord_user_id"));
+
+        Ok(discord_user_id)
+    }
+}
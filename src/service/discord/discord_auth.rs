@@ -1,18 +1,28 @@
 use crate::app::config::DiscordConfig;
 use crate::app::error::AppError;
 use crate::model::discord::User;
-use crate::model::session::UserRole;
+use crate::model::session::{DiscordTokens, Session, UserRole};
+use crate::service::SessionService;
 use crate::service::discord::error::Error;
-use oauth2::basic::{BasicClient, BasicErrorResponse, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse, BasicTokenResponse};
+use oauth2::basic::{
+    BasicClient, BasicErrorResponse, BasicErrorResponseType, BasicRevocationErrorResponse, BasicTokenIntrospectionResponse, BasicTokenResponse,
+};
 use oauth2::url::Url;
 use oauth2::{
-    AccessToken, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EndpointNotSet, EndpointSet, RedirectUrl, Scope,
-    StandardRevocableToken, TokenUrl,
+    AccessToken, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EndpointNotSet, EndpointSet, PkceCodeChallenge, PkceCodeVerifier,
+    RedirectUrl, RefreshToken, RequestTokenError, RevocationUrl, Scope, StandardRevocableToken, TokenResponse, TokenUrl,
 };
 use reqwest::Client;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, warn};
 
 pub type DiscordAuthClient = BasicClient;
 
+/// Waiting for the refresh lock of a session is given up after about the time the lock is held at most
+const REFRESH_LOCK_ATTEMPTS: usize = 50;
+const REFRESH_LOCK_RETRY_DELAY: Duration = Duration::from_millis(200);
+
 #[derive(Clone)]
 pub struct DiscordAuthService {
     http_client: Client,
@@ -26,7 +36,7 @@ pub struct DiscordAuthService {
         EndpointSet,
         EndpointNotSet,
         EndpointNotSet,
-        EndpointNotSet,
+        EndpointSet,
         EndpointSet,
     >,
     discord_config: DiscordConfig,
@@ -36,6 +46,7 @@ impl DiscordAuthService {
     pub fn new(discord_config: &DiscordConfig) -> Self {
         let discord_oauth_url = "https://discord.com/oauth2/authorize".to_string();
         let discord_token_url = format!("https://discord.com/api/v{}/oauth2/token", &discord_config.api_version);
+        let discord_revocation_url = format!("https://discord.com/api/v{}/oauth2/token/revoke", &discord_config.api_version);
 
         let http_client = reqwest::ClientBuilder::new()
             // Following redirects opens the client up to SSRF vulnerabilities.
@@ -47,6 +58,7 @@ impl DiscordAuthService {
             .set_client_secret(ClientSecret::new(discord_config.client_secret.to_string()))
             .set_auth_uri(AuthUrl::new(discord_oauth_url).expect("Failed to parse Discord OAuth URL"))
             .set_token_uri(TokenUrl::new(discord_token_url).expect("Failed to parse Discord token URL"))
+            .set_revocation_url(RevocationUrl::new(discord_revocation_url).expect("Failed to parse Discord revocation URL"))
             .set_redirect_uri(RedirectUrl::new(discord_config.redirect_url.to_string()).expect("Failed to parse Discord redirect URL"));
 
         Self {
@@ -56,16 +68,26 @@ impl DiscordAuthService {
         }
     }
 
-    /// Returns an oauth url for logging in with discord as well as a csrf token for safe oauth flow
-    pub fn init_auth(&self) -> (Url, CsrfToken) {
-        self.oauth_client.authorize_url(CsrfToken::new_random).add_scope(Scope::new(self.discord_config.scopes.clone())).url()
+    /// Returns an oauth url for logging in with discord as well as a csrf token and a PKCE verifier for safe oauth flow
+    pub fn init_auth(&self) -> (Url, CsrfToken, PkceCodeVerifier) {
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+        let (url, csrf_token) = self
+            .oauth_client
+            .authorize_url(CsrfToken::new_random)
+            .add_scope(Scope::new(self.discord_config.scopes.clone()))
+            .set_pkce_challenge(pkce_challenge)
+            .url();
+
+        (url, csrf_token, pkce_verifier)
     }
 
-    /// Exchanges oauth code for discord tokens
-    pub async fn exchanged_code_for_tokens(&self, code: &str) -> Result<BasicTokenResponse, AppError> {
+    /// Exchanges oauth code for discord tokens, proving with the PKCE verifier that this server started the login
+    pub async fn exchanged_code_for_tokens(&self, code: &str, pkce_verifier: PkceCodeVerifier) -> Result<BasicTokenResponse, AppError> {
         let token = self
             .oauth_client
             .exchange_code(AuthorizationCode::new(code.to_string()))
+            .set_pkce_verifier(pkce_verifier)
             .request_async(&self.http_client)
             .await
             .map_err(|e| Error::DiscordApiRequestError(e.to_string()))?;
@@ -73,6 +95,106 @@ impl DiscordAuthService {
         Ok(token)
     }
 
+    /// Exchanges a refresh token for new discord tokens
+    pub async fn exchange_refresh_token(&self, refresh_token: &str) -> Result<BasicTokenResponse, Error> {
+        self.oauth_client
+            .exchange_refresh_token(&RefreshToken::new(refresh_token.to_string()))
+            .request_async(&self.http_client)
+            .await
+            .map_err(|e| match e {
+                RequestTokenError::ServerResponse(response) if *response.error() == BasicErrorResponseType::InvalidGrant => {
+                    Error::RefreshTokenRejected
+                }
+                e => Error::DiscordApiRequestError(e.to_string()),
+            })
+    }
+
+    /// Makes sure the session has a discord access token, refreshing the tokens if the access token expired.
+    /// The rotated tokens are written back to the session. If discord rejects the refresh token the session is invalidated.
+    pub async fn ensure_access_token(&self, session_store: &SessionService, session_id: &str, session: Session) -> Result<Session, AppError> {
+        if session.discord.as_ref().is_none_or(|discord| discord.access_token.is_some()) {
+            return Ok(session);
+        }
+
+        // Requests of a session can run on any instance, the lock in redis makes sure only one of them refreshes
+        for _ in 0..REFRESH_LOCK_ATTEMPTS {
+            if let Some(lock_token) = session_store.try_lock_refresh(session_id).await? {
+                let result = self.refresh_session_tokens(session_store, session_id).await;
+                session_store.unlock_refresh(session_id, &lock_token).await?;
+                return result;
+            }
+
+            sleep(REFRESH_LOCK_RETRY_DELAY).await;
+
+            let session = session_store.validate_session(session_id).await?;
+            if session.discord.as_ref().is_none_or(|discord| discord.access_token.is_some()) {
+                return Ok(session);
+            }
+        }
+
+        warn!("{:<12} - Gave up waiting for the token refresh of another request", "DISCORD");
+        Err(Error::DiscordApiRequestError("Discord token refresh timed out".to_string()).into())
+    }
+
+    async fn refresh_session_tokens(&self, session_store: &SessionService, session_id: &str) -> Result<Session, AppError> {
+        // Another request of this session might have refreshed the tokens while waiting for the lock
+        let mut session = session_store.validate_session(session_id).await?;
+        let Some(discord) = &session.discord else {
+            return Ok(session);
+        };
+        if discord.access_token.is_some() {
+            return Ok(session);
+        }
+
+        debug!("{:<12} - Refreshing discord tokens of {}", "DISCORD", session.user.id);
+
+        let tokens = match self.exchange_refresh_token(&discord.refresh_token).await {
+            Ok(tokens) => tokens,
+            Err(Error::RefreshTokenRejected) => {
+                warn!(
+                    "{:<12} - Discord rejected the refresh token of {}, invalidating session",
+                    "DISCORD", session.user.id
+                );
+                session_store.invalidate_session(session_id).await?;
+                return Err(Error::RefreshTokenRejected.into());
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        session_store.save_discord_tokens(session_id, &tokens).await?;
+
+        session.discord = Some(DiscordTokens {
+            access_token: Some(tokens.access_token().secret().clone()),
+            refresh_token: tokens.refresh_token().map_or_else(|| discord.refresh_token.clone(), |token| token.secret().clone()),
+        });
+
+        Ok(session)
+    }
+
+    /// Revokes the discord tokens of removed sessions in the background. Discord ends the whole authorization of the app for the user,
+    /// so this is only called once the user has no session left. Failures are only logged, the sessions are gone either way.
+    pub fn revoke_tokens(&self, tokens: Vec<DiscordTokens>) {
+        if tokens.is_empty() {
+            return;
+        }
+
+        let auth = self.clone();
+        tokio::spawn(async move {
+            for tokens in tokens {
+                let token = StandardRevocableToken::RefreshToken(RefreshToken::new(tokens.refresh_token));
+
+                let result = match auth.oauth_client.revoke_token(token) {
+                    Ok(request) => request.request_async(&auth.http_client).await.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+
+                if let Err(e) = result {
+                    warn!("{:<12} - Failed to revoke discord tokens: {}", "DISCORD", e);
+                }
+            }
+        });
+    }
+
     /// Calls Discord’s `/users/@me` endpoint with the given access token to fetch user info.
     pub async fn get_discord_self_user_id(&self, access_token: &AccessToken) -> Result<String, Error> {
         let user = self
@@ -99,6 +221,10 @@ impl DiscordAuthService {
         }
     }
 
+    pub fn frontend_origin(&self) -> &str {
+        &self.discord_config.frontend_origin
+    }
+
     fn api_url_for(&self, path: &str) -> String {
         format!("https://discord.com/api/v{}/{}", self.discord_config.api_version, path)
     }
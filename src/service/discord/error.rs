@@ -6,6 +6,8 @@ use tracing::debug;
 pub enum Error {
     #[allow(dead_code)] // FIXME
     DiscordApiRequestError(String),
+    /// Discord no longer accepts the refresh token of a session, e.g. because the user deauthorized the app
+    RefreshTokenRejected,
 }
 
 impl From<Error> for AppError {
@@ -14,6 +16,7 @@ impl From<Error> for AppError {
 
         match value {
             Error::DiscordApiRequestError(_) => AppError::InternalServerError,
+            Error::RefreshTokenRejected => AppError::Unauthorized,
         }
     }
 }
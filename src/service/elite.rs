@@ -1,9 +1,34 @@
 use crate::app::error::AppError;
 use crate::db::error::DbError;
-use crate::model::elite::{Elite, EliteForUpdate, EliteStatus};
+use crate::model::elite::{Elite, EliteChange, EliteExportRow, EliteForCreate, EliteForUpdate, EliteRosterFilter, EliteStatus, ImportReport};
+use crate::service::IgnTrackerService;
 use crate::service::error::ServiceError::{CreatePreparedStatementError, DbConnectionError, NoFieldsToUpdate};
-use crate::web::error::Error::EliteNotFound;
+use crate::web::error::Error::{EliteAlreadyExists, EliteNotFound, EliteVersionMismatch};
 use deadpool_postgres::{GenericClient, Pool};
+use futures_util::StreamExt;
+use futures_util::stream::{self, BoxStream};
+use serde_json::{Map, Value};
+use tokio_postgres::error::SqlState;
+use tokio_postgres::types::ToSql;
+use uuid::Uuid;
+
+/// Roster filters shared by the page and count queries, the search matches a prefix of any current or past ign
+const ROSTER_CONDITIONS: &str = r"
+                e.status = ANY($1)
+                AND (
+                    $2::text IS NULL
+                    OR EXISTS (
+                        SELECT
+                            1
+                        FROM
+                            name_history nh
+                        WHERE
+                            nh.uuid = e.minecraft_uuid
+                            AND lower(nh.ign) LIKE lower(replace(replace(replace($2::text, '\', '\\'), '%', '\%'), '_', '\_')) || '%'
+                    )
+                )
+                AND ($3::text IS NULL OR e.country_code = $3::text)
+                AND ($4::bool IS NULL OR e.being_tracked = $4::bool)";
 
 #[derive(Clone)]
 pub struct EliteService {
@@ -47,9 +72,256 @@ impl EliteService {
         Ok(elites)
     }
 
-    pub async fn update_elite(&self, elite_id: i32, updated_elite: &EliteForUpdate) -> Result<Option<Elite>, AppError> {
+    /// Streams the elites with the given statuses row by row, optionally with their chronological name history.
+    /// The connection is moved into the stream, so it only returns to the pool once the export has been read.
+    pub async fn elites_export(
+        &self,
+        statuses: &[EliteStatus],
+        include_name_history: bool,
+    ) -> Result<BoxStream<'static, Result<EliteExportRow, AppError>>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached(
+                "
+                SELECT
+                    e.*,
+                    CASE WHEN $2 THEN (
+                        SELECT
+                            array_agg(nh.ign ORDER BY nh.timestamp, nh.id)
+                        FROM
+                            name_history nh
+                        WHERE
+                            nh.uuid = e.minecraft_uuid
+                    ) END AS name_history
+                FROM
+                    elites_with_ign e
+                WHERE
+                    e.status = ANY($1)
+                ORDER BY
+                    e.id
+                ",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let params: [&(dyn ToSql + Sync); 2] = [&statuses, &include_name_history];
+        let rows = con.query_raw(&stmt, params).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        let rows = stream::unfold((con, Box::pin(rows)), |(con, mut rows)| async move {
+            let row = rows.next().await?;
+            let row = row.map(|row| EliteExportRow::from(&row)).map_err(|e| DbError::QueryError(e.to_string()).into());
+            Some((row, (con, rows)))
+        });
+
+        Ok(rows.boxed())
+    }
+
+    /// Returns all active elites with a birthday set
+    pub async fn birthdays_all(&self) -> Result<Vec<Elite>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM elites_with_ign WHERE status = ANY($1) AND birthday IS NOT NULL")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let statuses = vec![EliteStatus::Staff, EliteStatus::Veteran, EliteStatus::Elite, EliteStatus::Trial];
+        let elites = con.query(&stmt, &[&statuses]).await.map_err(|_| DbConnectionError)?.iter().map(Elite::from).collect();
+
+        Ok(elites)
+    }
+
+    /// Returns a page of the roster matching the filter, ordered by the filter's sort key and `id`. Without `limit` every match is returned.
+    pub async fn search_elites(&self, filter: &EliteRosterFilter, limit: Option<i64>) -> Result<Vec<Elite>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        // Sort key, type and direction come from a fixed set, so only a handful of distinct statements are cached
+        let query = format!(
+            "
+            SELECT
+                *
+            FROM
+                elites_with_ign e
+            WHERE
+                {ROSTER_CONDITIONS}
+                AND ($5::text IS NULL OR ({key}, e.id) {after} (CAST($5::text AS {ty}), $6::int))
+            ORDER BY
+                {key} {order},
+                e.id {order}
+            LIMIT
+                $7
+            ",
+            key = filter.sort.sql_key(),
+            ty = filter.sort.sql_type(),
+            after = filter.order.sql_after(),
+            order = filter.order.sql(),
+        );
+
+        let stmt = con.prepare_cached(&query).await.map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let cursor = filter.cursor.as_ref();
+        let elites = con
+            .query(
+                &stmt,
+                &[
+                    &filter.statuses,
+                    &filter.search,
+                    &filter.country_code,
+                    &filter.being_tracked,
+                    &cursor.map(|cursor| cursor.key.as_str()),
+                    &cursor.map(|cursor| cursor.id),
+                    &limit,
+                ],
+            )
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .iter()
+            .map(Elite::from)
+            .collect();
+
+        Ok(elites)
+    }
+
+    /// Counts the elites matching the filter, ignoring its cursor
+    pub async fn count_elites(&self, filter: &EliteRosterFilter) -> Result<i64, AppError> {
         let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
 
+        let query = format!("SELECT count(*) AS total FROM elites_with_ign e WHERE {ROSTER_CONDITIONS}");
+        let stmt = con.prepare_cached(&query).await.map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let total = con
+            .query_one(&stmt, &[&filter.statuses, &filter.search, &filter.country_code, &filter.being_tracked])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .get("total");
+
+        Ok(total)
+    }
+
+    /// Updates an elite and records the changed fields in `elite_changes`, attributed to `actor_discord_id`.
+    /// If `expected_versions` is given the update is only applied if the current row version is one of them.
+    pub async fn update_elite(
+        &self,
+        elite_id: i32,
+        updated_elite: &EliteForUpdate,
+        actor_discord_id: &str,
+        expected_versions: Option<&[i32]>,
+    ) -> Result<Option<Elite>, AppError> {
+        let mut con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+        let tx = con.transaction().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Self::apply_update(&tx, elite_id, updated_elite, actor_discord_id, expected_versions).await?;
+
+        let query = "SELECT * FROM  elites_with_ign WHERE id = $1";
+        let stmt = tx.prepare_cached(query).await.map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let updated_elite = tx.query_opt(&stmt, &[&elite_id]).await.map_err(|e| DbError::QueryError(e.to_string()))?.map(|row| Elite::from(&row));
+
+        tx.commit().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(updated_elite)
+    }
+
+    /// Inserts a new elite with an already resolved uuid and ign, starts tracking the uuid and records the creation in `elite_changes`
+    pub async fn create_elite(
+        &self,
+        new_elite: &EliteForCreate,
+        minecraft_uuid: &Uuid,
+        ign: &str,
+        actor_discord_id: &str,
+    ) -> Result<Elite, AppError> {
+        let mut con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+        let tx = con.transaction().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        let elite_id = Self::insert_elite(&tx, new_elite, minecraft_uuid, ign, actor_discord_id).await?;
+
+        let select_stmt = tx
+            .prepare_cached("SELECT * FROM elites_with_ign WHERE id = $1")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let elite = tx
+            .query_one(&select_stmt, &[&elite_id])
+            .await
+            .map(|row| Elite::from(&row))
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        tx.commit().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(elite)
+    }
+
+    /// Applies all creates and updates of an import in one transaction, every change is recorded like a manual edit.
+    /// Creates need their ign resolved beforehand, updates fail if the elite changed since the import was planned.
+    pub async fn import_elites(&self, report: &ImportReport, actor_discord_id: &str) -> Result<(), AppError> {
+        let mut con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+        let tx = con.transaction().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        for update in &report.updates {
+            Self::apply_update(&tx, update.elite_id, &update.update, actor_discord_id, Some(&[update.version])).await?;
+        }
+
+        for create in &report.creates {
+            let (Some(minecraft_uuid), Some(ign)) = (&create.elite.minecraft_uuid, &create.elite.ign) else {
+                return Err(AppError::InternalServerError);
+            };
+            Self::insert_elite(&tx, &create.elite, minecraft_uuid, ign, actor_discord_id).await?;
+        }
+
+        tx.commit().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Returns the audit entries of a single elite, newest first
+    pub async fn changes_for_elite(&self, elite_id: i32, limit: i64, offset: i64) -> Result<Vec<EliteChange>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM elite_changes WHERE elite_id = $1 ORDER BY timestamp DESC, id DESC LIMIT $2 OFFSET $3")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let changes = con
+            .query(&stmt, &[&elite_id, &limit, &offset])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .iter()
+            .map(EliteChange::from)
+            .collect();
+
+        Ok(changes)
+    }
+
+    /// Returns the audit entries of all elites, newest first
+    pub async fn changes_all(&self, limit: i64, offset: i64) -> Result<Vec<EliteChange>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM elite_changes ORDER BY timestamp DESC, id DESC LIMIT $1 OFFSET $2")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let changes = con
+            .query(&stmt, &[&limit, &offset])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .iter()
+            .map(EliteChange::from)
+            .collect();
+
+        Ok(changes)
+    }
+
+    /// Updates an elite on the given client and records the changed fields, see [`Self::update_elite`]
+    async fn apply_update(
+        client: &impl GenericClient,
+        elite_id: i32,
+        updated_elite: &EliteForUpdate,
+        actor_discord_id: &str,
+        expected_versions: Option<&[i32]>,
+    ) -> Result<(), AppError> {
         let mut sets = Vec::new();
         let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = Vec::new();
         let mut param_index = 1;
@@ -84,20 +356,118 @@ impl EliteService {
             return Err(NoFieldsToUpdate.into());
         }
 
-        let query = format!("UPDATE elites SET {} WHERE id = ${}", sets.join(", "), param_index);
+        let before_stmt = client
+            .prepare_cached("SELECT to_jsonb(e) AS elite FROM elites e WHERE id = $1 FOR UPDATE")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let before: Value = client
+            .query_opt(&before_stmt, &[&elite_id])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .ok_or_else(|| EliteNotFound(format!("Elite with id {elite_id} does not exist.")))?
+            .get("elite");
+
+        let current_version = before.get("version").and_then(Value::as_i64).unwrap_or_default() as i32;
+        if expected_versions.is_some_and(|versions| !versions.contains(&current_version)) {
+            return Err(EliteVersionMismatch(current_version).into());
+        }
+
+        sets.push("version = version + 1".to_string());
+        let query = format!(
+            "UPDATE elites SET {} WHERE id = ${} RETURNING to_jsonb(elites) AS elite",
+            sets.join(", "),
+            param_index
+        );
         params.push(&elite_id);
 
-        let stmt = con.prepare_cached(&query).await.map_err(|e| CreatePreparedStatementError(e.to_string()))?;
-        let updated_rows = con.execute(&stmt, &params).await.map_err(|e| DbError::QueryError(e.to_string()))?;
-        if updated_rows == 0 {
-            return Err(EliteNotFound(format!("Elite with id {elite_id} does not exist.")).into());
+        let stmt = client.prepare_cached(&query).await.map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+        let after: Value = client.query_one(&stmt, &params).await.map_err(map_unique_violation)?.get("elite");
+
+        let changes = EliteChange::diff(&before, &after);
+        if !changes.is_empty() {
+            Self::record_change(client, elite_id, actor_discord_id, changes).await?;
         }
 
-        let query = "SELECT * FROM  elites_with_ign WHERE id = $1";
-        let stmt = con.prepare_cached(&query).await.map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+        Ok(())
+    }
 
-        let updated_elite = con.query_opt(&stmt, &[&elite_id]).await.map_err(|e| DbError::QueryError(e.to_string()))?.map(|row| Elite::from(&row));
+    /// Inserts an elite on the given client, starts tracking its uuid and records the creation, see [`Self::create_elite`]
+    async fn insert_elite(
+        client: &impl GenericClient,
+        new_elite: &EliteForCreate,
+        minecraft_uuid: &Uuid,
+        ign: &str,
+        actor_discord_id: &str,
+    ) -> Result<i32, AppError> {
+        // Only for a more helpful error, concurrent creates are caught by the unique indexes on the insert
+        let existing_stmt = client
+            .prepare_cached("SELECT id FROM elites WHERE minecraft_uuid = $1 OR discord_user_id = $2")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
 
-        Ok(updated_elite)
+        if let Some(row) = client
+            .query_opt(&existing_stmt, &[minecraft_uuid, &new_elite.discord_user_id])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+        {
+            let existing_id: i32 = row.get("id");
+            return Err(EliteAlreadyExists(format!("Elite with id {existing_id} already uses this uuid or discord user id.")).into());
+        }
+
+        let insert_stmt = client
+            .prepare_cached(
+                "
+                INSERT INTO elites (minecraft_uuid, discord_user_id, status, country_code, birthday)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id, to_jsonb(elites) AS elite
+            ",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let row = client
+            .query_one(
+                &insert_stmt,
+                &[
+                    minecraft_uuid,
+                    &new_elite.discord_user_id,
+                    &new_elite.status,
+                    &new_elite.country_code,
+                    &new_elite.birthday,
+                ],
+            )
+            .await
+            .map_err(map_unique_violation)?;
+        let elite_id: i32 = row.get("id");
+
+        Self::record_change(client, elite_id, actor_discord_id, EliteChange::diff(&Value::Null, &row.get("elite"))).await?;
+
+        IgnTrackerService::track_uuid(client, minecraft_uuid, ign).await?;
+
+        Ok(elite_id)
+    }
+
+    async fn record_change(client: &impl GenericClient, elite_id: i32, actor_discord_id: &str, changes: Map<String, Value>) -> Result<(), AppError> {
+        let stmt = client
+            .prepare_cached("INSERT INTO elite_changes (elite_id, actor_discord_id, changes, timestamp) VALUES ($1, $2, $3, NOW())")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        client
+            .execute(&stmt, &[&elite_id, &actor_discord_id, &Value::Object(changes)])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Another elite already uses the minecraft uuid or discord user id
+fn map_unique_violation(e: tokio_postgres::Error) -> AppError {
+    if e.code() == Some(&SqlState::UNIQUE_VIOLATION) {
+        EliteAlreadyExists("Another elite already uses this uuid or discord user id.".to_string()).into()
+    } else {
+        AppError::from(DbError::QueryError(e.to_string()))
     }
 }
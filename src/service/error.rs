@@ -7,6 +7,10 @@ pub enum ServiceError {
     #[allow(dead_code)] // FIXME
     CreatePreparedStatementError(String),
     NoFieldsToUpdate,
+    #[allow(dead_code)] // FIXME
+    TextureRequestError(String),
+    #[allow(dead_code)] // FIXME
+    InvalidSkinTexture(String),
 }
 
 impl From<ServiceError> for AppError {
@@ -17,6 +21,8 @@ impl From<ServiceError> for AppError {
             ServiceError::DbConnectionError => AppError::InternalServerError,
             ServiceError::CreatePreparedStatementError(_) => AppError::InternalServerError,
             ServiceError::NoFieldsToUpdate => AppError::BadRequest(Some("Provide at least 1 field to update".to_string())),
+            ServiceError::TextureRequestError(_) => AppError::InternalServerError,
+            ServiceError::InvalidSkinTexture(_) => AppError::InternalServerError,
         }
     }
 }
@@ -1,14 +1,123 @@
 use crate::app::error::AppError;
 use crate::db::error::DbError;
-use crate::model::recent_change::RecentChange;
+use crate::model::name_history::{NameHistoryEntry, PlayerNameHistory};
+use crate::model::recent_change::{RECENT_CHANGE_EVENT_TYPES, RecentChange, RecentChangesFilter};
+use crate::model::skin_history::SkinHistoryEntry;
+use crate::model::tracked_uuid::TrackedUuid;
 use crate::service::error::ServiceError::CreatePreparedStatementError;
-use deadpool_postgres::Pool;
+use crate::web::error::Error::{AlreadyTracked, TrackedUuidNotFound};
+use deadpool_postgres::{GenericClient, Pool};
+use std::cmp::Ordering;
+use tokio_postgres::types::ToSql;
+use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct IgnTrackerService {
     db_pool: Pool,
 }
 
+/// Inserts a `name_history` row for `$1` with ign `$2` and source `$3` unless `$2` already is the latest known ign.
+/// The source is `task` for igns seen by the tracker and `manual` for igns recorded when staff adds a uuid.
+const INSERT_NAME_IF_CHANGED: &str = "
+    INSERT INTO name_history (uuid, ign, timestamp, source)
+    SELECT $1::uuid, $2::text, NOW(), $3::text
+    WHERE $2::text IS DISTINCT FROM (
+        SELECT ign
+        FROM name_history
+        WHERE uuid = $1::uuid
+        ORDER BY timestamp DESC
+        LIMIT 1
+    )
+";
+
+/// Select list and source of the recent changes branch of an event type, with the main table aliased as `t`,
+/// and the condition that turns its rows into changes. The previous ign / texture is looked up per row,
+/// so the branch can be read in index order and stop after the requested rows.
+fn recent_changes_branch(event_type: &str) -> (&'static str, Option<&'static str>) {
+    match event_type {
+        "IGN_CHANGE" => (
+            "
+            t.id,
+            t.uuid,
+            prev.ign AS old_ign,
+            t.ign AS new_ign,
+            NULL::text AS old_texture_id,
+            NULL::text AS new_texture_id,
+            t.timestamp,
+            'IGN_CHANGE'::text AS event_type
+            FROM
+                name_history t
+                JOIN LATERAL (
+                    SELECT p.ign FROM name_history p
+                    WHERE p.uuid = t.uuid AND (p.timestamp, p.id) < (t.timestamp, t.id)
+                    ORDER BY p.timestamp DESC, p.id DESC
+                    LIMIT 1
+                ) prev ON TRUE
+            ",
+            Some("prev.ign <> t.ign"),
+        ),
+        "SKIN_CHANGE" => (
+            "
+            t.id,
+            t.uuid,
+            NULL::text AS old_ign,
+            nh_at_event.ign AS new_ign,
+            prev.texture_id AS old_texture_id,
+            t.texture_id AS new_texture_id,
+            t.timestamp,
+            'SKIN_CHANGE'::text AS event_type
+            FROM
+                skin_history t
+                JOIN LATERAL (
+                    SELECT p.texture_id FROM skin_history p
+                    WHERE p.uuid = t.uuid AND (p.timestamp, p.id) < (t.timestamp, t.id)
+                    ORDER BY p.timestamp DESC, p.id DESC
+                    LIMIT 1
+                ) prev ON TRUE
+                LEFT JOIN LATERAL (
+                    SELECT nh.ign FROM name_history nh
+                    WHERE nh.uuid = t.uuid AND nh.timestamp <= t.timestamp
+                    ORDER BY nh.timestamp DESC
+                    LIMIT 1
+                ) nh_at_event ON TRUE
+            ",
+            Some("prev.texture_id <> t.texture_id"),
+        ),
+        // Tracking operations, `add` and `remove` are separate branches so each has a fixed event type
+        _ => (
+            "
+            t.id,
+            t.uuid,
+            NULL::text AS old_ign,
+            nh_at_event.ign AS new_ign,
+            NULL::text AS old_texture_id,
+            NULL::text AS new_texture_id,
+            t.timestamp,
+            t.operation AS event_type
+            FROM
+                tracked_uuids_history t
+                LEFT JOIN LATERAL (
+                    SELECT nh.ign FROM name_history nh
+                    WHERE nh.uuid = t.uuid AND nh.timestamp <= t.timestamp
+                    ORDER BY nh.timestamp DESC
+                    LIMIT 1
+                ) nh_at_event ON TRUE
+            ",
+            match event_type {
+                "add" => Some("t.operation = 'add'"),
+                _ => Some("t.operation = 'remove'"),
+            },
+        ),
+    }
+}
+
+/// Which parts of a profile changed when it was recorded by [`IgnTrackerService::record_profile`]
+#[derive(Debug)]
+pub struct RecordedChanges {
+    pub ign_changed: bool,
+    pub skin_changed: bool,
+}
+
 impl IgnTrackerService {
     pub fn new(db_pool: Pool) -> Self {
         Self { db_pool }
@@ -16,67 +125,395 @@ impl IgnTrackerService {
 }
 
 impl IgnTrackerService {
-    pub async fn get_latest_changes(&self, limit: i64, offset: i64) -> Result<Vec<RecentChange>, AppError> {
+    /// Returns the most recent ign changes, skin changes and tracking operations matching the filter, newest first
+    pub async fn get_latest_changes(&self, filter: &RecentChangesFilter, limit: i64, offset: i64) -> Result<Vec<RecentChange>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+
+        // Every branch returns at most the rows needed for the requested page, so the union never reads more than that per table
+        let branch_limit = limit + offset;
+        let mut params: Vec<&(dyn ToSql + Sync)> = vec![&branch_limit];
+        let mut param_index = 2;
+
+        // Only the filters that are set are added, a catch-all `$n IS NULL OR ...` could not use the (timestamp, id) indexes
+        let mut conditions = Vec::new();
+        if let Some(ref uuid) = filter.uuid {
+            conditions.push(format!("t.uuid = ${}", param_index));
+            params.push(uuid);
+            param_index += 1;
+        }
+        if let Some(ref since) = filter.since {
+            conditions.push(format!("t.timestamp >= ${}::timestamptz", param_index));
+            params.push(since);
+            param_index += 1;
+        }
+        if let Some(ref until) = filter.until {
+            conditions.push(format!("t.timestamp < ${}::timestamptz", param_index));
+            params.push(until);
+            param_index += 1;
+        }
+
+        let cursor_params = filter.cursor.as_ref().map(|cursor| {
+            params.push(&cursor.timestamp);
+            params.push(&cursor.id);
+            param_index += 2;
+            (param_index - 2, param_index - 1)
+        });
+
+        let branches: Vec<String> = RECENT_CHANGE_EVENT_TYPES
+            .iter()
+            .filter(|event_type| filter.event_types.as_ref().is_none_or(|event_types| event_types.iter().any(|e| e == *event_type)))
+            .map(|event_type| {
+                let (from, branch_condition) = recent_changes_branch(event_type);
+                let mut branch_conditions = conditions.clone();
+                branch_conditions.extend(branch_condition.map(str::to_string));
+
+                // The feed is ordered by (timestamp, event_type, id) and the event type is fixed per branch,
+                // so the cursor turns into a condition on (timestamp, id) only
+                if let (Some(cursor), Some((timestamp, id))) = (&filter.cursor, cursor_params) {
+                    branch_conditions.push(match (*event_type).cmp(cursor.event_type.as_str()) {
+                        Ordering::Greater => format!("t.timestamp < ${timestamp}::timestamptz"),
+                        Ordering::Equal => format!("(t.timestamp, t.id) < (${timestamp}::timestamptz, ${id}::int)"),
+                        Ordering::Less => format!("t.timestamp <= ${timestamp}::timestamptz"),
+                    });
+                }
+
+                let where_clause = if branch_conditions.is_empty() {
+                    String::new()
+                } else {
+                    format!("WHERE {}", branch_conditions.join(" AND "))
+                };
+
+                format!("(SELECT {from} {where_clause} ORDER BY t.timestamp DESC, t.id DESC LIMIT $1)")
+            })
+            .collect();
+
+        if branches.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Event types are compared bytewise like in the cursor conditions above, independent of the database collation
+        let query = format!(
+            "
+            SELECT
+                *
+            FROM (
+                {}
+            ) AS changes
+            ORDER BY
+                changes.timestamp DESC,
+                changes.event_type COLLATE \"C\" DESC,
+                changes.id DESC
+            LIMIT
+                ${}
+            OFFSET
+                ${}
+            ",
+            branches.join(" UNION ALL "),
+            param_index,
+            param_index + 1,
+        );
+        params.push(&limit);
+        params.push(&offset);
+
+        let stmt = con.prepare_cached(&query).await.map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let rows = con.query(&stmt, &params).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        let latest_changes: Vec<RecentChange> = rows.into_iter().map(RecentChange::from).collect();
+
+        Ok(latest_changes)
+    }
+
+    /// Returns the tracked uuids that have not been checked for the longest time
+    pub async fn get_next_to_check(&self, limit: i64) -> Result<Vec<TrackedUuid>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM tracked_uuids_with_ign ORDER BY last_checked ASC LIMIT $1")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let tracked_uuids = con
+            .query(&stmt, &[&limit])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .iter()
+            .map(TrackedUuid::from)
+            .collect();
+
+        Ok(tracked_uuids)
+    }
+
+    /// Records the current ign and skin of a tracked uuid and marks it as checked.
+    /// `name_history` and `skin_history` rows are only inserted when the value differs from the latest known one.
+    pub async fn record_profile(&self, uuid: &Uuid, ign: &str, skin_texture_id: Option<&str>) -> Result<RecordedChanges, AppError> {
+        let mut con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+        let tx = con.transaction().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        let name_stmt = tx.prepare_cached(INSERT_NAME_IF_CHANGED).await.map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let ign_changed = tx.execute(&name_stmt, &[uuid, &ign, &"task"]).await.map_err(|e| DbError::QueryError(e.to_string()))? > 0;
+
+        let skin_changed = match skin_texture_id {
+            Some(texture_id) => {
+                let skin_stmt = tx
+                    .prepare_cached(
+                        "
+                        INSERT INTO skin_history (uuid, texture_id, timestamp, source)
+                        SELECT $1::uuid, $2::text, NOW(), 'task'
+                        WHERE $2::text IS DISTINCT FROM (
+                            SELECT texture_id
+                            FROM skin_history
+                            WHERE uuid = $1::uuid
+                            ORDER BY timestamp DESC
+                            LIMIT 1
+                        )
+                    ",
+                    )
+                    .await
+                    .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+                tx.execute(&skin_stmt, &[uuid, &texture_id]).await.map_err(|e| DbError::QueryError(e.to_string()))? > 0
+            }
+            None => false,
+        };
+
+        let update_stmt = tx
+            .prepare_cached("UPDATE tracked_uuids SET last_checked = NOW() WHERE uuid = $1")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        tx.execute(&update_stmt, &[uuid]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        tx.commit().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(RecordedChanges { ign_changed, skin_changed })
+    }
+
+    pub async fn get_skin_history(&self, uuid: &Uuid) -> Result<Vec<SkinHistoryEntry>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM skin_history WHERE uuid = $1 ORDER BY timestamp DESC")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let skin_history = con
+            .query(&stmt, &[uuid])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .iter()
+            .map(SkinHistoryEntry::from)
+            .collect();
+
+        Ok(skin_history)
+    }
+
+    /// Marks a tracked uuid as checked without recording a name
+    pub async fn touch_last_checked(&self, uuid: &Uuid) -> Result<(), AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("UPDATE tracked_uuids SET last_checked = NOW() WHERE uuid = $1")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        con.execute(&stmt, &[uuid]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Returns the latest recorded skin texture id of a tracked uuid
+    pub async fn get_latest_skin_texture_id(&self, uuid: &Uuid) -> Result<Option<String>, AppError> {
         let con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
 
         let stmt = con
             .prepare_cached(
                 "
                 SELECT
-                    uuid,
-                    old_ign,
-                    new_ign,
-                    timestamp,
-                    event_type
-                FROM (
-                    SELECT
-                        uuid,
-                        LAG(ign) OVER (PARTITION BY uuid ORDER BY timestamp) AS old_ign,
-                        ign AS new_ign,
-                        timestamp,
-                        'IGN_CHANGE' AS event_type
-                    FROM
-                        name_history
-                ) AS ign_changes
-                WHERE
-                    old_ign IS NOT NULL
-                    AND old_ign <> new_ign
-                UNION ALL
-                SELECT
-                    tuh.uuid,
-                    NULL AS old_ign,
-                    nh_at_event.ign AS new_ign,
-                    tuh.timestamp,
-                    tuh.operation AS event_type
+                    sh.texture_id
                 FROM
-                    tracked_uuids_history tuh
-                    LEFT JOIN LATERAL (
-                        SELECT
-                            nh.ign
-                        FROM
-                            name_history nh
-                        WHERE
-                            nh.uuid = tuh.uuid
-                            AND nh.timestamp <= tuh.timestamp
-                        ORDER BY
-                            nh.timestamp DESC
-                        LIMIT 1
-                    ) nh_at_event ON TRUE
+                    skin_history sh
+                WHERE
+                    sh.uuid = $1
+                    AND EXISTS (SELECT 1 FROM tracked_uuids tu WHERE tu.uuid = sh.uuid)
                 ORDER BY
-                    timestamp DESC
-                LIMIT
-                    $1
-                OFFSET
-                    $2;
+                    sh.timestamp DESC
+                LIMIT 1
             ",
             )
             .await
             .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
 
-        let rows = con.query(&stmt, &[&limit, &offset]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+        let texture_id = con.query_opt(&stmt, &[uuid]).await.map_err(|e| DbError::QueryError(e.to_string()))?.map(|row| row.get("texture_id"));
 
-        let latest_changes: Vec<RecentChange> = rows.into_iter().map(RecentChange::from).collect();
+        Ok(texture_id)
+    }
 
-        Ok(latest_changes)
+    pub async fn tracked_uuids_all(&self) -> Result<Vec<TrackedUuid>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM tracked_uuids_with_ign")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let tracked_uuids = con.query(&stmt, &[]).await.map_err(|e| DbError::QueryError(e.to_string()))?.iter().map(TrackedUuid::from).collect();
+
+        Ok(tracked_uuids)
+    }
+
+    /// Starts tracking a uuid and records the `add` operation as well as its current ign
+    pub async fn add_tracked_uuid(&self, uuid: &Uuid, ign: &str) -> Result<TrackedUuid, AppError> {
+        let mut con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+        let tx = con.transaction().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        if !Self::track_uuid(&tx, uuid, ign).await? {
+            return Err(AlreadyTracked(format!("Uuid {uuid} is already being tracked.")).into());
+        }
+
+        let select_stmt = tx
+            .prepare_cached("SELECT * FROM tracked_uuids_with_ign WHERE uuid = $1")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let tracked_uuid = tx
+            .query_one(&select_stmt, &[uuid])
+            .await
+            .map(|row| TrackedUuid::from(&row))
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        tx.commit().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(tracked_uuid)
+    }
+
+    /// Inserts a uuid into `tracked_uuids` together with its `add` history entry and records its current ign.
+    /// Meant to be run inside a transaction. Returns `false` if the uuid was already tracked, in which case only the ign is recorded.
+    pub async fn track_uuid(client: &impl GenericClient, uuid: &Uuid, ign: &str) -> Result<bool, AppError> {
+        let insert_stmt = client
+            .prepare_cached(
+                "
+                INSERT INTO tracked_uuids (uuid, last_checked)
+                VALUES ($1, NOW())
+                ON CONFLICT (uuid) DO NOTHING
+            ",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let inserted = client.execute(&insert_stmt, &[uuid]).await.map_err(|e| DbError::QueryError(e.to_string()))? > 0;
+
+        if inserted {
+            let history_stmt = client
+                .prepare_cached("INSERT INTO tracked_uuids_history (uuid, operation, timestamp) VALUES ($1, 'add', NOW())")
+                .await
+                .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+            client.execute(&history_stmt, &[uuid]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+        }
+
+        let name_stmt = client.prepare_cached(INSERT_NAME_IF_CHANGED).await.map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        client.execute(&name_stmt, &[uuid, &ign, &"manual"]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(inserted)
+    }
+
+    /// Stops tracking a uuid and records the `remove` operation
+    pub async fn remove_tracked_uuid(&self, uuid: &Uuid) -> Result<(), AppError> {
+        let mut con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+        let tx = con.transaction().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        let delete_stmt = tx
+            .prepare_cached("DELETE FROM tracked_uuids WHERE uuid = $1")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let deleted = tx.execute(&delete_stmt, &[uuid]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+        if deleted == 0 {
+            return Err(TrackedUuidNotFound(format!("Uuid {uuid} is not being tracked.")).into());
+        }
+
+        let history_stmt = tx
+            .prepare_cached("INSERT INTO tracked_uuids_history (uuid, operation, timestamp) VALUES ($1, 'remove', NOW())")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        tx.execute(&history_stmt, &[uuid]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        tx.commit().await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Returns the full name history of a player, `None` if no name was ever recorded for the uuid
+    pub async fn get_name_history(&self, uuid: &Uuid) -> Result<Option<PlayerNameHistory>, AppError> {
+        let name_history = self.name_histories_for(&[*uuid]).await?.pop();
+
+        Ok(name_history)
+    }
+
+    /// Returns the name histories of all players that currently use or once used the given ign (case-insensitive).
+    /// Players currently using the ign come first, followed by the most recent previous owners.
+    pub async fn find_name_histories_by_ign(&self, ign: &str) -> Result<Vec<PlayerNameHistory>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT DISTINCT uuid FROM name_history WHERE LOWER(ign) = LOWER($1)")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let uuids: Vec<Uuid> = con
+            .query(&stmt, &[&ign])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .iter()
+            .map(|row| row.get("uuid"))
+            .collect();
+
+        let mut name_histories = self.name_histories_for(&uuids).await?;
+
+        let last_used =
+            |history: &PlayerNameHistory| history.names.iter().rev().find(|period| period.ign.eq_ignore_ascii_case(ign)).and_then(|period| period.to);
+        name_histories.sort_by(|a, b| {
+            let a_current = a.current_ign.as_deref().is_some_and(|current| current.eq_ignore_ascii_case(ign));
+            let b_current = b.current_ign.as_deref().is_some_and(|current| current.eq_ignore_ascii_case(ign));
+
+            b_current.cmp(&a_current).then_with(|| last_used(b).cmp(&last_used(a)))
+        });
+
+        Ok(name_histories)
+    }
+
+    async fn name_histories_for(&self, uuids: &[Uuid]) -> Result<Vec<PlayerNameHistory>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbError::ConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM name_history WHERE uuid = ANY($1) ORDER BY uuid, timestamp ASC, id ASC")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let rows = con.query(&stmt, &[&uuids]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        let mut name_histories = Vec::new();
+        let mut entries: Vec<NameHistoryEntry> = Vec::new();
+
+        for entry in rows.iter().map(NameHistoryEntry::from) {
+            if let Some(previous) = entries.last()
+                && previous.uuid != entry.uuid
+            {
+                let uuid = previous.uuid;
+                name_histories.push(PlayerNameHistory::from_entries(uuid, std::mem::take(&mut entries)));
+            }
+            entries.push(entry);
+        }
+
+        if let Some(first) = entries.first() {
+            let uuid = first.uuid;
+            name_histories.push(PlayerNameHistory::from_entries(uuid, entries));
+        }
+
+        Ok(name_histories)
     }
 }
@@ -0,0 +1,62 @@
+use crate::app::error::AppError;
+use crate::db::error::DbError;
+use crate::model::impersonation::{ImpersonationAction, ImpersonationEvent};
+use crate::service::error::ServiceError::{CreatePreparedStatementError, DbConnectionError};
+use deadpool_postgres::Pool;
+
+#[derive(Clone)]
+pub struct ImpersonationService {
+    db_pool: Pool,
+}
+
+impl ImpersonationService {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl ImpersonationService {
+    pub async fn record(
+        &self,
+        impersonator_id: &str,
+        target_user_id: &str,
+        action: ImpersonationAction,
+        method: Option<&str>,
+        path: Option<&str>,
+    ) -> Result<(), AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached(
+                "INSERT INTO impersonation_events (impersonator_id, target_user_id, action, method, path, timestamp) VALUES ($1, $2, $3, $4, $5, now())",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        con.execute(&stmt, &[&impersonator_id, &target_user_id, &action.to_string(), &method, &path])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Newest events first
+    pub async fn events(&self, limit: i64, offset: i64) -> Result<Vec<ImpersonationEvent>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM impersonation_events ORDER BY timestamp DESC, id DESC LIMIT $1 OFFSET $2")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let events = con
+            .query(&stmt, &[&limit, &offset])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .iter()
+            .map(ImpersonationEvent::from)
+            .collect();
+
+        Ok(events)
+    }
+}
@@ -0,0 +1,153 @@
+use crate::app::config::MinecraftConfig;
+use crate::app::error::AppError;
+use crate::db::error::DbError;
+use crate::model::elite::{Elite, EliteStatus};
+use crate::model::minecraft::{GroupAssignment, MinecraftServer, OpsEntry, WhitelistEntry};
+use crate::service::error::ServiceError::{CreatePreparedStatementError, DbConnectionError};
+use crate::service::token::{generate_token, hash_token};
+use crate::web::error::Error::{MinecraftServerAlreadyExists, MinecraftServerNotFound};
+use deadpool_postgres::Pool;
+use tokio_postgres::error::SqlState;
+use tracing::warn;
+
+#[derive(Clone)]
+pub struct MinecraftService {
+    db_pool: Pool,
+    config: MinecraftConfig,
+}
+
+impl MinecraftService {
+    pub fn new(db_pool: Pool, config: &MinecraftConfig) -> Self {
+        Self {
+            db_pool,
+            config: config.clone(),
+        }
+    }
+}
+
+impl MinecraftService {
+    pub async fn servers_all(&self) -> Result<Vec<MinecraftServer>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM minecraft_servers ORDER BY id")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let servers = con.query(&stmt, &[]).await.map_err(|e| DbError::QueryError(e.to_string()))?.iter().map(MinecraftServer::from).collect();
+
+        Ok(servers)
+    }
+
+    /// Registers a server and returns it together with its api token, the token can not be retrieved again
+    pub async fn create_server(&self, name: &str) -> Result<(MinecraftServer, String), AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let token = generate_token();
+
+        let stmt = con
+            .prepare_cached("INSERT INTO minecraft_servers (name, token_hash) VALUES ($1, $2) RETURNING *")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let server = con.query_one(&stmt, &[&name, &hash_token(&token)]).await.map_err(|e| {
+            if e.code() == Some(&SqlState::UNIQUE_VIOLATION) {
+                MinecraftServerAlreadyExists(format!("A server named {name} already exists.")).into()
+            } else {
+                AppError::from(DbError::QueryError(e.to_string()))
+            }
+        })?;
+
+        Ok((MinecraftServer::from(&server), token))
+    }
+
+    pub async fn remove_server(&self, server_id: i32) -> Result<(), AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("DELETE FROM minecraft_servers WHERE id = $1")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let deleted = con.execute(&stmt, &[&server_id]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+        if deleted == 0 {
+            return Err(MinecraftServerNotFound(format!("Server with id {server_id} does not exist.")).into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the server owning an api token and marks it as used
+    pub async fn authenticate(&self, token: &str) -> Result<Option<MinecraftServer>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("UPDATE minecraft_servers SET last_used_at = now() WHERE token_hash = $1 RETURNING *")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let server = con
+            .query_opt(&stmt, &[&hash_token(token)])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .map(|row| MinecraftServer::from(&row));
+
+        Ok(server)
+    }
+
+    /// Whitelist entries for every elite that is not an ex elite
+    pub fn whitelist(&self, elites: &[Elite]) -> Vec<WhitelistEntry> {
+        with_ign(elites)
+            .filter(|(elite, _)| elite.status != EliteStatus::None)
+            .map(|(elite, ign)| WhitelistEntry {
+                uuid: elite.minecraft_uuid,
+                name: ign.to_string(),
+            })
+            .collect()
+    }
+
+    /// Operator entries for staff
+    pub fn ops(&self, elites: &[Elite]) -> Vec<OpsEntry> {
+        with_ign(elites)
+            .filter(|(elite, _)| elite.status == EliteStatus::Staff)
+            .map(|(elite, ign)| OpsEntry {
+                uuid: elite.minecraft_uuid,
+                name: ign.to_string(),
+                level: self.config.op_level,
+                bypasses_player_limit: true,
+            })
+            .collect()
+    }
+
+    /// LuckPerms group of every elite that is not an ex elite, mapped from its status
+    pub fn group_assignments(&self, elites: &[Elite]) -> Vec<GroupAssignment> {
+        with_ign(elites)
+            .filter_map(|(elite, ign)| {
+                let group = match elite.status {
+                    EliteStatus::Staff => &self.config.group_staff,
+                    EliteStatus::Veteran => &self.config.group_veteran,
+                    EliteStatus::Elite => &self.config.group_elite,
+                    EliteStatus::Trial => &self.config.group_trial,
+                    EliteStatus::None => return None,
+                };
+
+                Some(GroupAssignment {
+                    uuid: elite.minecraft_uuid,
+                    name: ign.to_string(),
+                    group: group.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The server ignores entries without a name, so elites without a known ign are skipped
+fn with_ign(elites: &[Elite]) -> impl Iterator<Item = (&Elite, &str)> {
+    elites.iter().filter_map(|elite| match &elite.ign {
+        Some(ign) => Some((elite, ign.as_str())),
+        None => {
+            warn!("{:<12} - Skipping elite {} without a known ign", "MINECRAFT", elite.id);
+            None
+        }
+    })
+}
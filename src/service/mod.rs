@@ -1,11 +1,28 @@
+mod api_key;
+mod auth_event;
+mod avatar;
+mod calendar;
 mod discord;
 mod elite;
 mod error;
 mod ign_tracker;
+mod impersonation;
+mod minecraft;
+mod mojang;
+mod role;
 mod session;
+mod token;
 
+pub use api_key::ApiKeyService;
+pub use auth_event::AuthEventService;
+pub use avatar::AvatarService;
+pub use calendar::CalendarService;
 pub use discord::discord_api::DiscordApiService;
 pub use discord::discord_auth::DiscordAuthService;
 pub use elite::EliteService;
 pub use ign_tracker::IgnTrackerService;
+pub use impersonation::ImpersonationService;
+pub use minecraft::MinecraftService;
+pub use mojang::mojang_api::MojangApiService;
+pub use role::RoleService;
 pub use session::SessionService;
@@ -0,0 +1,75 @@
+use crate::app::config::TrackerConfig;
+use crate::model::mojang::{Profile, TexturesPayload};
+use crate::service::mojang::error::Error;
+use crate::service::mojang::error::Error::{InvalidTexturesProperty, MojangApiRequestError};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use tracing::debug;
+use uuid::Uuid;
+
+#[derive(Clone)]
+pub struct MojangApiService {
+    client: Client,
+    profile_api_url: String,
+    name_api_url: String,
+}
+
+impl MojangApiService {
+    pub fn new(config: &TrackerConfig) -> Self {
+        Self {
+            client: Client::new(),
+            profile_api_url: config.profile_api_url.trim_end_matches('/').to_string(),
+            name_api_url: config.name_api_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Fetches the current profile (name and textures) for the given uuid
+    pub async fn get_profile(&self, uuid: &Uuid) -> Result<Option<Profile>, Error> {
+        let url = format!("{}/session/minecraft/profile/{}", self.profile_api_url, uuid.simple());
+        let profile = self.request::<Profile>(self.client.get(url)).await?;
+
+        Ok(profile)
+    }
+
+    /// Resolves an ign to the profile currently owning it. The returned profile carries no properties.
+    pub async fn get_profile_by_name(&self, name: &str) -> Result<Option<Profile>, Error> {
+        let url = format!("{}/users/profiles/minecraft/{}", self.name_api_url, name);
+        let profile = self.request::<Profile>(self.client.get(url)).await?;
+
+        Ok(profile)
+    }
+
+    /// Decodes the base64 `textures` property of a profile and returns the skin texture id.
+    /// Returns `None` if the player uses a default skin.
+    pub fn skin_texture_id(profile: &Profile) -> Result<Option<String>, Error> {
+        let Some(property) = profile.properties.iter().find(|property| property.name == "textures") else {
+            return Ok(None);
+        };
+
+        let decoded = STANDARD.decode(&property.value).map_err(|e| InvalidTexturesProperty(e.to_string()))?;
+        let payload = serde_json::from_slice::<TexturesPayload>(&decoded).map_err(|e| InvalidTexturesProperty(e.to_string()))?;
+
+        Ok(payload.textures.skin.as_ref().and_then(|skin| skin.texture_id()).map(str::to_string))
+    }
+
+    async fn request<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<Option<T>, Error> {
+        let response = request.send().await.map_err(|e| MojangApiRequestError(e.to_string()))?;
+
+        // The session server answers unknown uuids with 204 instead of 404
+        if response.status() == StatusCode::NOT_FOUND || response.status() == StatusCode::NO_CONTENT {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            debug!("Failed to get response: {:?}", response);
+            return Err(MojangApiRequestError(response.status().to_string()));
+        }
+
+        response.json::<T>().await.map(Some).map_err(|e| {
+            debug!("Failed to deserialize response: {:?}", e);
+            MojangApiRequestError(e.to_string())
+        })
+    }
+}
@@ -0,0 +1,103 @@
+use crate::app::error::AppError;
+use crate::db::error::DbError;
+use crate::model::permission::{Permission, RoleMapping, RoleMappingForUpdate};
+use crate::model::session::UserRole;
+use crate::service::error::ServiceError::{CreatePreparedStatementError, DbConnectionError};
+use crate::web::error::Error::RoleMappingNotFound;
+use deadpool_postgres::Pool;
+use strum::IntoEnumIterator;
+
+#[derive(Clone)]
+pub struct RoleService {
+    db_pool: Pool,
+}
+
+impl RoleService {
+    pub fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+}
+
+impl RoleService {
+    pub async fn mappings_all(&self) -> Result<Vec<RoleMapping>, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("SELECT * FROM role_permissions ORDER BY name")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let mappings = con.query(&stmt, &[]).await.map_err(|e| DbError::QueryError(e.to_string()))?.iter().map(RoleMapping::from).collect();
+
+        Ok(mappings)
+    }
+
+    /// Creates or replaces the permission set of a discord role
+    pub async fn save_mapping(&self, discord_role_id: &str, mapping: &RoleMappingForUpdate, actor_discord_id: &str) -> Result<RoleMapping, AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let permissions: Vec<String> = mapping.permissions.iter().map(ToString::to_string).collect();
+
+        let stmt = con
+            .prepare_cached(
+                "
+                INSERT INTO role_permissions (discord_role_id, name, permissions, updated_by, updated_at)
+                VALUES ($1, $2, $3, $4, now())
+                ON CONFLICT (discord_role_id) DO UPDATE
+                SET name = EXCLUDED.name, permissions = EXCLUDED.permissions, updated_by = EXCLUDED.updated_by, updated_at = EXCLUDED.updated_at
+                RETURNING *
+                ",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let row = con
+            .query_one(&stmt, &[&discord_role_id, &mapping.name, &permissions, &actor_discord_id])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?;
+
+        Ok(RoleMapping::from(&row))
+    }
+
+    pub async fn remove_mapping(&self, discord_role_id: &str) -> Result<(), AppError> {
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached("DELETE FROM role_permissions WHERE discord_role_id = $1")
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let deleted = con.execute(&stmt, &[&discord_role_id]).await.map_err(|e| DbError::QueryError(e.to_string()))?;
+        if deleted == 0 {
+            return Err(RoleMappingNotFound(format!("Discord role {discord_role_id} has no permissions.")).into());
+        }
+
+        Ok(())
+    }
+
+    /// Permissions of a guild member. Staff has every permission, everyone else the union of the sets mapped to their discord roles.
+    pub async fn permissions_for(&self, role: &UserRole, discord_role_ids: &[String]) -> Result<Vec<Permission>, AppError> {
+        if *role == UserRole::Staff {
+            return Ok(Permission::iter().collect());
+        }
+
+        let con = self.db_pool.get().await.map_err(|_| DbConnectionError)?;
+
+        let stmt = con
+            .prepare_cached(
+                "SELECT DISTINCT unnest(permissions) AS permission FROM role_permissions WHERE discord_role_id = ANY($1) ORDER BY permission",
+            )
+            .await
+            .map_err(|e| CreatePreparedStatementError(e.to_string()))?;
+
+        let permissions = con
+            .query(&stmt, &[&discord_role_ids])
+            .await
+            .map_err(|e| DbError::QueryError(e.to_string()))?
+            .iter()
+            .filter_map(|row| row.get::<_, String>("permission").parse().ok())
+            .collect();
+
+        Ok(permissions)
+    }
+}
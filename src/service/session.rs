@@ -1,17 +1,21 @@
 use crate::app::config::SessionConfig;
 use crate::app::constants::{
-    CSRF_TOKEN_KEY, DISCORD_ACCESS_TOKEN_KEY, DISCORD_REFRESH_TOKEN_KEY, FIVE_MINUTES, ONE_MONTH, SESSION_COOKIE_NAME, SESSION_KEY_PREFIX,
-    USER_ID_KEY, USER_ROLE_KEY,
+    CREATED_AT_KEY, CSRF_TOKEN_KEY, DISCORD_ACCESS_TOKEN_KEY, DISCORD_REFRESH_TOKEN_KEY, FIVE_MINUTES, IMPERSONATION_EXPIRES_AT_KEY,
+    IMPERSONATION_PERMISSIONS_KEY, IMPERSONATION_ROLE_KEY, IMPERSONATION_USER_ID_KEY, IP_KEY, LAST_SEEN_AT_KEY, ONE_MINUTE, ONE_MONTH,
+    PERMISSIONS_KEY, PKCE_VERIFIER_KEY, REFRESH_LOCK_KEY_PREFIX, ROLE_CHECK_RETRY_AT_KEY, ROLE_CHECKED_AT_KEY, SESSION_COOKIE_NAME,
+    SESSION_KEY_PREFIX, USER_AGENT_KEY, USER_ID_KEY, USER_ROLE_KEY, USER_SESSIONS_KEY_PREFIX,
 };
 use crate::app::error::AppError;
-use crate::model::session::{Session, UserRole};
+use crate::model::permission::Permission;
+use crate::model::session::{DiscordTokens, Session, SessionInfo, SessionUser};
+use crate::service::token::{generate_token, hash_token};
 use crate::web::error::Error;
-use hex::encode;
+use chrono::{DateTime, Utc};
 use oauth2::basic::BasicTokenResponse;
-use oauth2::{CsrfToken, TokenResponse};
-use rand::RngCore;
+use oauth2::{CsrfToken, PkceCodeVerifier, TokenResponse};
 use redis::aio::ConnectionManager;
-use redis::{AsyncCommands, ErrorKind, ExpireOption, pipe};
+use redis::{AsyncCommands, ErrorKind, ExpireOption, FromRedisValue, Script, ToRedisArgs, Value, pipe};
+use std::cmp::Reverse;
 use std::sync::Arc;
 use time::{Duration, OffsetDateTime};
 use tower_cookies::Cookie;
@@ -22,6 +26,7 @@ use tracing::debug;
 pub struct SessionService {
     redis: Arc<ConnectionManager>,
     pub secure_cookie: bool,
+    role_check_interval: chrono::Duration,
 }
 
 impl SessionService {
@@ -29,19 +34,21 @@ impl SessionService {
         Self {
             redis: Arc::new(redis),
             secure_cookie: session_config.secure_cookie,
+            role_check_interval: chrono::Duration::seconds(session_config.role_check_interval_secs),
         }
     }
 }
 
 impl SessionService {
-    pub async fn init_session(&self, csrf_token: &CsrfToken) -> Result<String, AppError> {
+    pub async fn init_session(&self, csrf_token: &CsrfToken, pkce_verifier: &PkceCodeVerifier) -> Result<String, AppError> {
         let mut con = self.redis.as_ref().clone();
 
         let session_id = self.generate_session_id();
-        let session_key = format!("{}:{}", SESSION_KEY_PREFIX, session_id);
+        let session_key = session_key(&session_id);
 
         let _: () = pipe()
             .hset(&session_key, CSRF_TOKEN_KEY, csrf_token.secret())
+            .hset(&session_key, PKCE_VERIFIER_KEY, pkce_verifier.secret())
             .expire(&session_key, FIVE_MINUTES)
             .query_async(&mut con)
             .await
@@ -50,45 +57,196 @@ impl SessionService {
         Ok(session_id)
     }
 
-    pub async fn validate_session(&self, session_id: &String) -> Result<Session, AppError> {
+    pub async fn validate_session(&self, session_id: &str) -> Result<Session, AppError> {
         let session = self.get_session_by_id(session_id).await?.ok_or(Error::SessionNotFound)?;
 
         Ok(session)
     }
 
-    pub async fn validate_init_session(&self, session_id: &String, csrf_token: &CsrfToken) -> Result<(), AppError> {
+    /// Checks the pre login session against the state of the callback and returns the PKCE verifier of the login
+    pub async fn validate_init_session(&self, session_id: &str, csrf_token: &CsrfToken) -> Result<PkceCodeVerifier, AppError> {
         let mut con = self.redis.as_ref().clone();
-        let session_key = format!("{}:{}", SESSION_KEY_PREFIX, session_id);
+        let session_key = session_key(session_id);
 
         // Check if the session exists with cookie session id and state csrf_token
-        match con.hget::<_, _, Option<String>>(&session_key, CSRF_TOKEN_KEY).await {
-            Ok(Some(token)) if token.as_str() == csrf_token.secret() => Ok(()),
+        match con.hget::<_, _, (Option<String>, Option<String>)>(&session_key, &[CSRF_TOKEN_KEY, PKCE_VERIFIER_KEY]).await {
+            Ok((Some(token), Some(pkce_verifier))) if token.as_str() == csrf_token.secret() => Ok(PkceCodeVerifier::new(pkce_verifier)),
             Ok(_) => Err(Error::SessionNotFound.into()),
             Err(err) => Err(Error::RedisOperationError(err.to_string()).into()),
         }
     }
 
-    pub async fn invalidate_session(&self, session_id: &String) -> Result<(), AppError> {
+    /// Removes a session and returns its discord tokens so they can be revoked, pre login sessions have none.
+    /// Revoking ends the discord authorization of every session of the user, so the tokens are only returned for the last live session.
+    pub async fn invalidate_session(&self, session_id: &str) -> Result<Option<DiscordTokens>, AppError> {
         let mut con = self.redis.as_ref().clone();
-        let session_key = format!("{}:{}", SESSION_KEY_PREFIX, session_id);
+        let session_key = session_key(session_id);
 
-        let _: () = con.del::<_, ()>(&session_key).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+        let session = con.hgetall::<_, Value>(&session_key).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+        let session = Session::from_redis_value(&session).ok();
+
+        let mut pipe = pipe();
+        pipe.del(&session_key).ignore();
+        if let Some(session) = &session {
+            pipe.srem(user_sessions_key(&session.user.id), session_hash(session_id)).ignore();
+        }
+
+        let _: () = pipe.query_async(&mut con).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        let Some(session) = session else {
+            return Ok(None);
+        };
+        if self.has_live_sessions(&session.user.id).await? {
+            return Ok(None);
+        }
+
+        Ok(session.discord)
+    }
+
+    pub async fn refresh_session_ttl(&self, session_id: &str, user_id: &str) -> Result<(), AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let session_key = session_key(session_id);
+
+        let _: () = pipe()
+            .expire(&session_key, ONE_MONTH)
+            .expire(user_sessions_key(user_id), ONE_MONTH)
+            .query_async(&mut con)
+            .await
+            .map_err(|e| Error::RedisOperationError(e.to_string()))?;
 
         Ok(())
     }
 
-    pub async fn refresh_session_ttl(&self, session_id: &str) -> Result<(), AppError> {
+    /// Updates the last seen time of a session, at most once a minute to not write on every request
+    pub async fn touch_session(&self, session_id: &str, session: &Session) -> Result<(), AppError> {
+        let last_seen_at = session.device.as_ref().and_then(|device| device.last_seen_at);
+        if last_seen_at.is_some_and(|last_seen_at| last_seen_at + chrono::Duration::seconds(ONE_MINUTE) > Utc::now()) {
+            return Ok(());
+        }
+
         let mut con = self.redis.as_ref().clone();
-        let session_key = format!("{}:{}", SESSION_KEY_PREFIX, session_id);
+        let session_key = session_key(session_id);
 
-        let _: () = con.expire(&session_key, ONE_MONTH).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+        hset_if_exists(&mut con, &session_key, &[(LAST_SEEN_AT_KEY, Utc::now().timestamp())]).await?;
 
         Ok(())
     }
 
-    pub async fn get_session_by_id(&self, session_id: &String) -> Result<Option<Session>, AppError> {
+    /// All live sessions of a user, newest first. Expired sessions are removed from the index on the way.
+    pub async fn user_sessions(&self, user_id: &str, current_session_id: Option<&str>) -> Result<Vec<SessionInfo>, AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let index_key = user_sessions_key(user_id);
+
+        let session_hashes = con.smembers::<_, Vec<String>>(&index_key).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+        if session_hashes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = pipe();
+        for session_hash in &session_hashes {
+            pipe.hgetall(format!("{}:{}", SESSION_KEY_PREFIX, session_hash));
+        }
+        let values: Vec<Value> = pipe.query_async(&mut con).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        let current_session_hash = current_session_id.map(session_hash);
+
+        let mut sessions = Vec::new();
+        let mut expired = Vec::new();
+        for (session_hash, value) in session_hashes.into_iter().zip(values) {
+            match Session::from_redis_value(&value) {
+                Ok(Session { device: Some(device), .. }) => {
+                    let current = current_session_hash.as_ref() == Some(&session_hash);
+                    sessions.push(SessionInfo::new(session_hash, device, current));
+                }
+                _ => expired.push(session_hash),
+            }
+        }
+
+        if !expired.is_empty() {
+            let _: () = con.srem(&index_key, &expired).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+        }
+
+        sessions.sort_by_key(|session| Reverse(session.created_at));
+
+        Ok(sessions)
+    }
+
+    /// Invalidates a session of a user by the id shown in [`Self::user_sessions`] and returns its discord tokens if it was the last live session
+    pub async fn invalidate_user_session(&self, user_id: &str, public_id: &str) -> Result<Option<DiscordTokens>, AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let index_key = user_sessions_key(user_id);
+
+        let owned = con.sismember::<_, _, bool>(&index_key, public_id).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+        if !owned {
+            return Err(Error::UserSessionNotFound(format!("Session {public_id} does not exist.")).into());
+        }
+
+        let session_key = format!("{}:{}", SESSION_KEY_PREFIX, public_id);
+
+        let (session, (), ()): (Value, (), ()) = pipe()
+            .hgetall(&session_key)
+            .del(&session_key)
+            .srem(&index_key, public_id)
+            .query_async(&mut con)
+            .await
+            .map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        if self.has_live_sessions(user_id).await? {
+            return Ok(None);
+        }
+
+        Ok(Session::from_redis_value(&session).ok().and_then(|session| session.discord))
+    }
+
+    /// Whether any session in the index of the user still exists, entries of expired sessions are ignored
+    async fn has_live_sessions(&self, user_id: &str) -> Result<bool, AppError> {
         let mut con = self.redis.as_ref().clone();
-        let session_key = format!("{}:{}", SESSION_KEY_PREFIX, session_id);
+
+        let session_hashes = con
+            .smembers::<_, Vec<String>>(user_sessions_key(user_id))
+            .await
+            .map_err(|e| Error::RedisOperationError(e.to_string()))?;
+        if session_hashes.is_empty() {
+            return Ok(false);
+        }
+
+        let mut pipe = pipe();
+        for session_hash in &session_hashes {
+            pipe.exists(format!("{}:{}", SESSION_KEY_PREFIX, session_hash));
+        }
+        let exists: Vec<bool> = pipe.query_async(&mut con).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        Ok(exists.into_iter().any(|exists| exists))
+    }
+
+    /// Invalidates every session of a user and returns the discord tokens of the removed sessions, so the authorization can be revoked
+    pub async fn invalidate_user_sessions(&self, user_id: &str) -> Result<Vec<DiscordTokens>, AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let index_key = user_sessions_key(user_id);
+
+        let session_hashes = con.smembers::<_, Vec<String>>(&index_key).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        let mut pipe = pipe();
+        for session_hash in &session_hashes {
+            let session_key = format!("{}:{}", SESSION_KEY_PREFIX, session_hash);
+            pipe.hgetall(&session_key).del(&session_key).ignore();
+        }
+        pipe.del(&index_key).ignore();
+
+        let sessions: Vec<Value> = pipe.query_async(&mut con).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        let tokens = sessions
+            .iter()
+            .filter_map(|session| Session::from_redis_value(session).ok())
+            .filter_map(|session| session.discord)
+            .collect();
+
+        Ok(tokens)
+    }
+
+    pub async fn get_session_by_id(&self, session_id: &str) -> Result<Option<Session>, AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let session_key = session_key(session_id);
 
         let session_exists = con.exists::<_, bool>(&session_key).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
 
@@ -115,45 +273,271 @@ impl SessionService {
             .build()
     }
 
+    /// Turns the pre login session into an authenticated one. The session id is rotated so an id planted before the login
+    /// can not be used afterwards, the new id is returned and has to replace the cookie.
     pub async fn save_session(
         &self,
-        session_id: &String,
+        init_session_id: &str,
         tokens: &BasicTokenResponse,
-        user_id: &String,
-        user_role: &UserRole,
-    ) -> Result<(), AppError> {
+        user: &SessionUser,
+        ip: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<String, AppError> {
         let mut con = self.redis.as_ref().clone();
-        let session_key = format!("{}:{}", SESSION_KEY_PREFIX, session_id);
+        let init_session_key = session_key(init_session_id);
+        let session_id = self.generate_session_id();
+        let session_key = session_key(&session_id);
+        let index_key = user_sessions_key(&user.id);
 
-        let session_fields = [
-            (USER_ID_KEY, user_id),
-            (USER_ROLE_KEY, &user_role.to_string()),
-            (DISCORD_ACCESS_TOKEN_KEY, tokens.access_token().secret()),
-            (DISCORD_REFRESH_TOKEN_KEY, tokens.refresh_token().unwrap().secret()),
+        let role = user.role.to_string();
+        let permissions = join_permissions(&user.permissions);
+        let now = Utc::now().timestamp().to_string();
+        let mut session_fields = vec![
+            (USER_ID_KEY, user.id.as_str()),
+            (USER_ROLE_KEY, &role),
+            (PERMISSIONS_KEY, &permissions),
+            (ROLE_CHECKED_AT_KEY, &now),
+            (CREATED_AT_KEY, &now),
+            (LAST_SEEN_AT_KEY, &now),
         ];
+        if let Some(ip) = ip {
+            session_fields.push((IP_KEY, ip));
+        }
+        if let Some(user_agent) = user_agent {
+            session_fields.push((USER_AGENT_KEY, user_agent));
+        }
 
-        let discord_access_token_expires_in = i64::try_from(tokens.expires_in().unwrap().as_secs()).unwrap() - 5;
-
-        debug!("Saving session - {} - {}", &user_id, &user_role.to_string());
+        debug!("Saving session - {} - {}", &user.id, &role);
         let _: () = pipe()
+            .del(&init_session_key)
+            .ignore()
             .hset_multiple(&session_key, &session_fields)
+            .hset_multiple(&session_key, &discord_token_fields(tokens))
             .hexpire(
                 &session_key,
-                discord_access_token_expires_in,
+                discord_access_token_expires_in(tokens),
                 ExpireOption::NONE,
                 DISCORD_ACCESS_TOKEN_KEY,
             )
             .expire(&session_key, ONE_MONTH)
+            .sadd(&index_key, session_hash(&session_id))
+            .expire(&index_key, ONE_MONTH)
+            .query_async(&mut con)
+            .await
+            .map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        Ok(session_id)
+    }
+
+    /// Replaces the discord tokens of a session with the rotated tokens of a refresh, the session ttl is kept
+    pub async fn save_discord_tokens(&self, session_id: &str, tokens: &BasicTokenResponse) -> Result<(), AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let session_key = session_key(session_id);
+
+        // A session logged out during the refresh stays gone, its new refresh token is dropped with it
+        if !hset_if_exists(&mut con, &session_key, &discord_token_fields(tokens)).await? {
+            return Err(Error::SessionNotFound.into());
+        }
+
+        // Expiring a field of a removed key does nothing, so this can not recreate the session either
+        let _: () = con
+            .hexpire(
+                &session_key,
+                discord_access_token_expires_in(tokens),
+                ExpireOption::NONE,
+                DISCORD_ACCESS_TOKEN_KEY,
+            )
+            .await
+            .map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Puts a "view as" overlay on a session until `expires_at`. The fields expire on their own so a forgotten overlay does not stick around.
+    pub async fn start_impersonation(&self, session_id: &str, target: &SessionUser, expires_at: DateTime<Utc>) -> Result<(), AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let session_key = session_key(session_id);
+
+        let overlay_fields = [
+            (IMPERSONATION_USER_ID_KEY, target.id.clone()),
+            (IMPERSONATION_ROLE_KEY, target.role.to_string()),
+            (IMPERSONATION_PERMISSIONS_KEY, join_permissions(&target.permissions)),
+            (IMPERSONATION_EXPIRES_AT_KEY, expires_at.timestamp().to_string()),
+        ];
+
+        if !hset_if_exists(&mut con, &session_key, &overlay_fields).await? {
+            return Err(Error::SessionNotFound.into());
+        }
+
+        let _: () = con
+            .hexpire_at(&session_key, expires_at.timestamp(), ExpireOption::NONE, &IMPERSONATION_KEYS)
+            .await
+            .map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    pub async fn stop_impersonation(&self, session_id: &str) -> Result<(), AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let session_key = session_key(session_id);
+
+        let _: () = con.hdel(&session_key, &IMPERSONATION_KEYS).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Whether the role of a session was trusted long enough and has to be checked against the guild again
+    pub fn is_role_check_due(&self, session: &Session) -> bool {
+        if session.is_api_key() {
+            return false;
+        }
+
+        if session.role_check_retry_at.is_some_and(|retry_at| retry_at > Utc::now()) {
+            return false;
+        }
+
+        session.role_checked_at.is_none_or(|checked_at| checked_at + self.role_check_interval <= Utc::now())
+    }
+
+    /// Holds off the role check of a session for a minute after discord failed, so an outage or rate limit is not made worse
+    pub async fn postpone_role_check(&self, session_id: &str) -> Result<(), AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let session_key = session_key(session_id);
+
+        let retry_at = Utc::now().timestamp() + ONE_MINUTE;
+        hset_if_exists(&mut con, &session_key, &[(ROLE_CHECK_RETRY_AT_KEY, retry_at)]).await?;
+
+        Ok(())
+    }
+
+    /// Stores the role and permissions of a session after they were checked against the guild
+    pub async fn save_role_check(&self, session_id: &str, user: &SessionUser) -> Result<(), AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let session_key = session_key(session_id);
+
+        let session_fields = [
+            (USER_ROLE_KEY, user.role.to_string()),
+            (PERMISSIONS_KEY, join_permissions(&user.permissions)),
+            (ROLE_CHECKED_AT_KEY, Utc::now().timestamp().to_string()),
+        ];
+
+        hset_if_exists(&mut con, &session_key, &session_fields).await?;
+
+        Ok(())
+    }
+
+    /// Takes the discord token refresh lock of a session, shared by all instances of the api. Returns the token needed to release it,
+    /// `None` if another request holds the lock. The lock expires on its own in case the holder dies.
+    pub async fn try_lock_refresh(&self, session_id: &str) -> Result<Option<String>, AppError> {
+        let mut con = self.redis.as_ref().clone();
+        let lock_token = generate_token();
+
+        let acquired: Option<String> = redis::cmd("SET")
+            .arg(refresh_lock_key(session_id))
+            .arg(&lock_token)
+            .arg("NX")
+            .arg("PX")
+            .arg(REFRESH_LOCK_MILLIS)
             .query_async(&mut con)
             .await
             .map_err(|e| Error::RedisOperationError(e.to_string()))?;
 
+        Ok(acquired.map(|_| lock_token))
+    }
+
+    /// Releases the refresh lock if it is still held with `lock_token`, a lock that expired and was taken by another request is kept
+    pub async fn unlock_refresh(&self, session_id: &str, lock_token: &str) -> Result<(), AppError> {
+        let mut con = self.redis.as_ref().clone();
+
+        let _: i64 = Script::new(RELEASE_LOCK_SCRIPT)
+            .key(refresh_lock_key(session_id))
+            .arg(lock_token)
+            .invoke_async(&mut con)
+            .await
+            .map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
         Ok(())
     }
 
+    /// 256 random bits, hex encoded
     pub fn generate_session_id(&self) -> String {
-        let mut bytes = [0u8; 512];
-        rand::rng().fill_bytes(&mut bytes);
-        encode(bytes)
+        generate_token()
     }
 }
+
+/// Session fields of the "view as" overlay
+const IMPERSONATION_KEYS: [&str; 4] = [
+    IMPERSONATION_USER_ID_KEY,
+    IMPERSONATION_ROLE_KEY,
+    IMPERSONATION_PERMISSIONS_KEY,
+    IMPERSONATION_EXPIRES_AT_KEY,
+];
+
+fn join_permissions(permissions: &[Permission]) -> String {
+    permissions.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
+
+/// Sets hash fields only if the key exists, so a session removed while a request was working on it is not recreated without a ttl
+const HSET_IF_EXISTS_SCRIPT: &str = r"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    return 0
+end
+redis.call('HSET', KEYS[1], unpack(ARGV))
+return 1
+";
+
+/// Deletes a lock only if it still holds the token of its owner
+const RELEASE_LOCK_SCRIPT: &str = r"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+";
+
+/// Upper bound of a discord token refresh, after which the lock is given up
+const REFRESH_LOCK_MILLIS: i64 = 10_000;
+
+/// Returns whether the fields were written
+async fn hset_if_exists<V: ToRedisArgs>(con: &mut ConnectionManager, key: &str, fields: &[(&str, V)]) -> Result<bool, AppError> {
+    let script = Script::new(HSET_IF_EXISTS_SCRIPT);
+    let mut invocation = script.key(key);
+    for (field, value) in fields {
+        invocation.arg(*field).arg(value);
+    }
+
+    let written: i64 = invocation.invoke_async(con).await.map_err(|e| Error::RedisOperationError(e.to_string()))?;
+
+    Ok(written == 1)
+}
+
+fn refresh_lock_key(session_id: &str) -> String {
+    format!("{}:{}", REFRESH_LOCK_KEY_PREFIX, session_hash(session_id))
+}
+
+fn user_sessions_key(user_id: &str) -> String {
+    format!("{}:{}", USER_SESSIONS_KEY_PREFIX, user_id)
+}
+
+/// Sessions are stored under the hash of their id, so the keys in a redis dump can not be used as cookies.
+/// The hash is also the public id a session is listed and revoked by.
+fn session_hash(session_id: &str) -> String {
+    hash_token(session_id)
+}
+
+fn session_key(session_id: &str) -> String {
+    format!("{}:{}", SESSION_KEY_PREFIX, session_hash(session_id))
+}
+
+/// Access and refresh token fields of a session. Discord rotates the refresh token on every refresh.
+fn discord_token_fields(tokens: &BasicTokenResponse) -> Vec<(&'static str, &str)> {
+    let mut fields = vec![(DISCORD_ACCESS_TOKEN_KEY, tokens.access_token().secret().as_str())];
+    if let Some(refresh_token) = tokens.refresh_token() {
+        fields.push((DISCORD_REFRESH_TOKEN_KEY, refresh_token.secret().as_str()));
+    }
+    fields
+}
+
+/// Seconds until the access token expires, shortened a bit so it is never used right at its expiry
+fn discord_access_token_expires_in(tokens: &BasicTokenResponse) -> i64 {
+    i64::try_from(tokens.expires_in().unwrap().as_secs()).unwrap() - 5
+}
@@ -0,0 +1,15 @@
+use hex::encode;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Generates a random token for a client that can not use the session cookie.
+/// The token is only handed out once, the database only stores its hash.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    encode(bytes)
+}
+
+pub fn hash_token(token: &str) -> String {
+    encode(Sha256::digest(token.as_bytes()))
+}
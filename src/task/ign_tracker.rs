@@ -0,0 +1,81 @@
+use crate::app::config::TrackerConfig;
+use crate::service::{IgnTrackerService, MojangApiService};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{MissedTickBehavior, interval, sleep};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Spawns the background worker that keeps `name_history` and `skin_history` up to date for all tracked uuids.
+pub fn spawn_ign_tracker(ign_tracker: IgnTrackerService, mojang: MojangApiService, config: &TrackerConfig) -> JoinHandle<()> {
+    let poll_interval = Duration::from_secs(config.poll_interval_secs);
+    let request_delay = Duration::from_millis(config.request_delay_ms);
+    let batch_size = config.batch_size;
+
+    tokio::spawn(async move {
+        info!("{:<12} - Started ign tracker, polling every {:?}", "TASK", poll_interval);
+
+        let mut ticker = interval(poll_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            poll_batch(&ign_tracker, &mojang, batch_size, request_delay).await;
+        }
+    })
+}
+
+async fn poll_batch(ign_tracker: &IgnTrackerService, mojang: &MojangApiService, batch_size: i64, request_delay: Duration) {
+    let tracked_uuids = match ign_tracker.get_next_to_check(batch_size).await {
+        Ok(tracked_uuids) => tracked_uuids,
+        Err(e) => {
+            warn!("{:<12} - Failed to load tracked uuids: {:?}", "TASK", e);
+            return;
+        }
+    };
+
+    debug!("{:<12} - Checking {} tracked uuid(s)", "TASK", tracked_uuids.len());
+
+    for tracked in tracked_uuids {
+        match mojang.get_profile(&tracked.uuid).await {
+            Ok(Some(profile)) => {
+                let skin_texture_id = MojangApiService::skin_texture_id(&profile).unwrap_or_else(|e| {
+                    warn!("{:<12} - Failed to decode textures for {}: {}", "TASK", tracked.uuid, e);
+                    None
+                });
+
+                match ign_tracker.record_profile(&tracked.uuid, &profile.name, skin_texture_id.as_deref()).await {
+                    Ok(changes) => {
+                        if changes.ign_changed {
+                            info!("{:<12} - Recorded new ign {} for {}", "TASK", profile.name, tracked.uuid);
+                        }
+                        if changes.skin_changed {
+                            info!("{:<12} - Recorded new skin for {}", "TASK", tracked.uuid);
+                        }
+                    }
+                    Err(e) => {
+                        warn!("{:<12} - Failed to record profile for {}: {:?}", "TASK", tracked.uuid, e);
+                        touch_last_checked(ign_tracker, &tracked.uuid).await;
+                    }
+                }
+            }
+            Ok(None) => {
+                debug!("{:<12} - No profile found for {}", "TASK", tracked.uuid);
+                touch_last_checked(ign_tracker, &tracked.uuid).await;
+            }
+            // A failing uuid moves to the back of the queue as well, otherwise it would be picked first every round
+            Err(e) => {
+                warn!("{:<12} - Failed to fetch profile for {}: {}", "TASK", tracked.uuid, e);
+                touch_last_checked(ign_tracker, &tracked.uuid).await;
+            }
+        }
+
+        sleep(request_delay).await;
+    }
+}
+
+async fn touch_last_checked(ign_tracker: &IgnTrackerService, uuid: &Uuid) {
+    if let Err(e) = ign_tracker.touch_last_checked(uuid).await {
+        warn!("{:<12} - Failed to update last_checked for {}: {:?}", "TASK", uuid, e);
+    }
+}
@@ -0,0 +1,3 @@
+mod ign_tracker;
+
+pub use ign_tracker::spawn_ign_tracker;
@@ -0,0 +1,76 @@
+use serde_json::json;
+use strum_macros::Display;
+
+/// Why a discord login failed, sent to the frontend as `reason` of a `discordAuthFailed` message
+#[derive(Display, Debug, Clone, Copy)]
+pub enum AuthFailure {
+    /// The user cancelled on the discord consent screen
+    #[strum(serialize = "access-denied")]
+    AccessDenied,
+    /// The callback does not belong to a login started by this browser, or the login took too long
+    #[strum(serialize = "invalid-state")]
+    InvalidState,
+    #[strum(serialize = "not-in-guild")]
+    NotInGuild,
+    #[strum(serialize = "not-elite")]
+    NotElite,
+    #[strum(serialize = "discord-error")]
+    DiscordError,
+    #[strum(serialize = "server-error")]
+    ServerError,
+}
+
+/// Renders the page the discord login popup lands on. It posts the result to the frontend window that opened it and closes itself.
+/// Messages are only delivered to `frontend_origin`, so other sites opening the popup learn nothing about the login.
+pub fn auth_callback_page(frontend_origin: &str, result: Result<(), AuthFailure>) -> String {
+    let (message, text) = match result {
+        Ok(()) => (json!({ "type": "discordAuthComplete" }), "Login successful, you can close this window."),
+        Err(reason) => (
+            json!({ "type": "discordAuthFailed", "reason": reason.to_string() }),
+            "Login failed, you can close this window.",
+        ),
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+    <body>
+        <script>
+            window.opener?.postMessage({message}, {origin});
+            window.close();
+            window.history.replaceState({{}}, document.title, '/auth/discord/callback');
+        </script>
+        <p>{text}</p>
+    </body>
+</html>
+"#,
+        message = script_value(&message),
+        origin = script_value(&json!(frontend_origin)),
+    )
+}
+
+/// JSON is valid javascript, only a closing script tag inside a string could break out of the script element
+fn script_value(value: &serde_json::Value) -> String {
+    value.to_string().replace("</", "<\\/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_closing_script_tags() {
+        let page = auth_callback_page("https://example.com</script><script>alert(1)//", Ok(()));
+
+        assert!(!page.contains("</script><script>"));
+        assert!(page.contains(r#""https://example.com<\/script><script>alert(1)//""#));
+    }
+
+    #[test]
+    fn includes_failure_reason() {
+        let page = auth_callback_page("https://example.com", Err(AuthFailure::NotInGuild));
+
+        assert!(page.contains(r#""reason":"not-in-guild""#));
+        assert!(page.contains("Login failed"));
+    }
+}
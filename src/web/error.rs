@@ -1,11 +1,12 @@
+use crate::app::constants::AVATAR_SIZES;
 use crate::app::error::AppError;
+use crate::model::api_key::ApiKeyScope;
 use tracing::trace;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Clone)]
 pub enum Error {
     // Auth/Discord related errors
-    NoCodeInDiscordCallbackPath,
-    NoStateInDiscordCallbackPath,
     #[allow(dead_code)] // FIXME
     DiscordApiError(String),
 
@@ -14,6 +15,7 @@ pub enum Error {
     SessionNotFound,
     #[allow(dead_code)] // FIXME
     InvalidSession(String),
+    UserSessionNotFound(String),
 
     // Redis errors
     #[allow(dead_code)] // FIXME
@@ -22,7 +24,37 @@ pub enum Error {
     NotInElite,
     NotInEliteGuild,
     EliteNotFound(String),
-    StaffOnly,
+    AvatarNotFound(String),
+    InvalidAvatarSize(u32),
+    TrackedUuidNotFound(String),
+    AlreadyTracked(String),
+    PlayerNotFound(String),
+    InvalidIgn(String),
+    InvalidTrackedUuidForCreate,
+    InvalidEliteForCreate,
+    EliteAlreadyExists(String),
+    EliteVersionMismatch(i32),
+    DiscordUserNotInEliteGuild(String),
+    InvalidCursor,
+    InvalidEventType(String),
+    InvalidEliteStatus(String),
+    InvalidExportColumn(String),
+    InvalidImportCsv(String),
+    InvalidServerToken,
+    InvalidApiKey,
+    MissingApiKeyScope,
+    ApiKeyScopeNotGrantable(ApiKeyScope),
+    ApiKeyNotFound(String),
+    InvalidMinecraftServerName,
+    MinecraftServerAlreadyExists(String),
+    MinecraftServerNotFound(String),
+    InvalidBirthdayRange(i64),
+    InvalidCalendarToken,
+    MissingPermission,
+    ImpersonationReadOnly,
+    ImpersonationForbidden,
+    InvalidImpersonationTarget(String),
+    RoleMappingNotFound(String),
 }
 
 impl From<Error> for AppError {
@@ -30,17 +62,51 @@ impl From<Error> for AppError {
         trace!("{:<12} - {value:?}", "FROM_APP_ERR");
 
         match value {
-            Error::NoCodeInDiscordCallbackPath => AppError::BadRequest(None),
-            Error::NoStateInDiscordCallbackPath => AppError::BadRequest(None),
             Error::DiscordApiError(_) => AppError::InternalServerError,
             Error::RedisOperationError(_) => AppError::InternalServerError,
             Error::SessionCookieNotFound => AppError::Unauthorized,
             Error::SessionNotFound => AppError::Unauthorized,
             Error::InvalidSession(_) => AppError::Unauthorized,
+            Error::UserSessionNotFound(msg) => AppError::NotFound(Some(msg)),
             Error::NotInElite => AppError::Unauthorized,
             Error::NotInEliteGuild => AppError::Unauthorized,
             Error::EliteNotFound(msg) => AppError::NotFound(Some(msg)),
-            Error::StaffOnly => AppError::Unauthorized,
+            Error::AvatarNotFound(msg) => AppError::NotFound(Some(msg)),
+            Error::TrackedUuidNotFound(msg) => AppError::NotFound(Some(msg)),
+            Error::AlreadyTracked(msg) => AppError::Conflict(Some(msg)),
+            Error::PlayerNotFound(msg) => AppError::NotFound(Some(msg)),
+            Error::InvalidIgn(ign) => AppError::BadRequest(Some(format!("Invalid ign {ign}"))),
+            Error::InvalidTrackedUuidForCreate => AppError::BadRequest(Some("Provide either uuid or ign".to_string())),
+            Error::InvalidEliteForCreate => AppError::BadRequest(Some("Provide either minecraft_uuid or ign".to_string())),
+            Error::EliteAlreadyExists(msg) => AppError::Conflict(Some(msg)),
+            Error::EliteVersionMismatch(current) => {
+                AppError::PreconditionFailed(Some(format!("Elite was modified in the meantime, current version is {current}")))
+            }
+            Error::DiscordUserNotInEliteGuild(user_id) => AppError::BadRequest(Some(format!("Discord user {user_id} is not in the elite guild"))),
+            Error::InvalidCursor => AppError::BadRequest(Some("Invalid cursor".to_string())),
+            Error::InvalidEventType(event_type) => AppError::BadRequest(Some(format!("Invalid event type {event_type}"))),
+            Error::InvalidBirthdayRange(days) => AppError::BadRequest(Some(format!("Invalid days {days}"))),
+            Error::InvalidCalendarToken => AppError::Unauthorized,
+            Error::InvalidServerToken => AppError::Unauthorized,
+            Error::InvalidApiKey => AppError::Unauthorized,
+            Error::MissingApiKeyScope => AppError::Unauthorized,
+            Error::ApiKeyScopeNotGrantable(scope) => AppError::Forbidden(Some(format!("Missing the permissions to grant scope {scope}"))),
+            Error::ApiKeyNotFound(msg) => AppError::NotFound(Some(msg)),
+            Error::InvalidMinecraftServerName => AppError::BadRequest(Some("Server name must be between 1 and 64 characters".to_string())),
+            Error::MinecraftServerAlreadyExists(msg) => AppError::Conflict(Some(msg)),
+            Error::MinecraftServerNotFound(msg) => AppError::NotFound(Some(msg)),
+            Error::InvalidImportCsv(msg) => AppError::BadRequest(Some(format!("Invalid csv: {msg}"))),
+            Error::InvalidExportColumn(column) => AppError::BadRequest(Some(format!("Invalid export column {column}"))),
+            Error::InvalidEliteStatus(status) => AppError::BadRequest(Some(format!("Invalid status {status}"))),
+            Error::InvalidAvatarSize(size) => {
+                let sizes = AVATAR_SIZES.map(|size| size.to_string()).join(", ");
+                AppError::BadRequest(Some(format!("Invalid avatar size {size}, must be one of {sizes}")))
+            }
+            Error::MissingPermission => AppError::Forbidden(None),
+            Error::ImpersonationReadOnly => AppError::Forbidden(Some("Changes are not allowed while viewing as another user".to_string())),
+            Error::ImpersonationForbidden => AppError::Forbidden(Some("This page is not available while viewing as another user".to_string())),
+            Error::InvalidImpersonationTarget(msg) => AppError::BadRequest(Some(msg)),
+            Error::RoleMappingNotFound(msg) => AppError::NotFound(Some(msg)),
         }
     }
 }
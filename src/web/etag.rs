@@ -0,0 +1,44 @@
+use axum::http::{HeaderMap, HeaderValue};
+use sha2::{Digest, Sha256};
+
+/// Strong etag of a versioned resource
+pub fn version_etag(version: i32) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{version}\"")).expect("etag is a valid header value")
+}
+
+/// Strong etag derived from the serialized representation of a resource
+pub fn content_etag(content: &[u8]) -> HeaderValue {
+    let hash = Sha256::digest(content);
+    HeaderValue::from_str(&format!("\"{}\"", hex::encode(&hash[..16]))).expect("etag is a valid header value")
+}
+
+/// Parses the entity tags of an `If-Match` or `If-None-Match` header.
+/// Returns `None` if the header is missing or `*`, weak tags are returned without their `W/` prefix.
+pub fn entity_tags(headers: &HeaderMap, name: &str) -> Option<Vec<String>> {
+    let value = headers.get(name)?.to_str().ok()?.trim();
+
+    if value == "*" {
+        return None;
+    }
+
+    let tags = value.split(',').map(|tag| tag.trim().trim_start_matches("W/").to_string()).filter(|tag| !tag.is_empty()).collect();
+
+    Some(tags)
+}
+
+/// Returns the versions listed in an `If-Match` header, `None` if any version is acceptable
+pub fn if_match_versions(headers: &HeaderMap) -> Option<Vec<i32>> {
+    let tags = entity_tags(headers, axum::http::header::IF_MATCH.as_str())?;
+
+    Some(tags.iter().filter_map(|tag| tag.trim_matches('"').parse().ok()).collect())
+}
+
+/// Whether the `If-None-Match` header of a request matches the current etag of the resource
+pub fn if_none_match(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    if headers.get(axum::http::header::IF_NONE_MATCH).is_some_and(|value| value == "*") {
+        return true;
+    }
+
+    let etag = etag.to_str().unwrap_or_default();
+    entity_tags(headers, axum::http::header::IF_NONE_MATCH.as_str()).is_some_and(|tags| tags.iter().any(|tag| tag == etag))
+}
@@ -0,0 +1,130 @@
+use crate::model::elite::{EliteExportRow, ExportFormat, NAME_HISTORY_COLUMN};
+use serde_json::Value;
+
+/// Serializes export rows one at a time, so an export can be streamed without holding the whole roster in memory
+pub struct ExportWriter {
+    format: ExportFormat,
+    columns: Vec<String>,
+    rows_written: usize,
+}
+
+impl ExportWriter {
+    pub fn new(format: ExportFormat, columns: Vec<String>) -> Self {
+        Self {
+            format,
+            columns,
+            rows_written: 0,
+        }
+    }
+
+    /// Content written before the first row
+    pub fn header(&self) -> String {
+        match self.format {
+            ExportFormat::Csv => csv_line(&self.columns),
+            ExportFormat::Json => "[".to_string(),
+            ExportFormat::Ndjson => String::new(),
+        }
+    }
+
+    pub fn row(&mut self, row: &EliteExportRow) -> String {
+        self.rows_written += 1;
+        let values = self.values(row);
+
+        match self.format {
+            ExportFormat::Csv => csv_line(&values.iter().map(|(_, value)| csv_value(value)).collect::<Vec<_>>()),
+            ExportFormat::Json => {
+                let separator = if self.rows_written > 1 { "," } else { "" };
+                format!("{separator}\n{}", json_object(&values))
+            }
+            ExportFormat::Ndjson => format!("{}\n", json_object(&values)),
+        }
+    }
+
+    /// Content written after the last row. It does not depend on the written rows,
+    /// so it can be emitted after the writer has been moved into the row stream.
+    pub fn footer(format: ExportFormat) -> String {
+        match format {
+            ExportFormat::Json => "\n]\n".to_string(),
+            ExportFormat::Csv | ExportFormat::Ndjson => String::new(),
+        }
+    }
+
+    fn values<'a>(&'a self, row: &EliteExportRow) -> Vec<(&'a str, Value)> {
+        let elite = serde_json::to_value(&row.elite).unwrap_or_default();
+
+        self.columns
+            .iter()
+            .map(|column| {
+                let value = match column.as_str() {
+                    NAME_HISTORY_COLUMN => row.name_history.as_ref().map(|names| Value::from(names.clone())).unwrap_or_default(),
+                    column => elite.get(column).cloned().unwrap_or_default(),
+                };
+                (column.as_str(), value)
+            })
+            .collect()
+    }
+}
+
+/// Builds the object by hand to keep the selected column order
+fn json_object(values: &[(&str, Value)]) -> String {
+    let fields: Vec<String> = values.iter().map(|(column, value)| format!("{}:{}", Value::from(*column), value)).collect();
+
+    format!("{{{}}}", fields.join(","))
+}
+
+/// Flattens a json value into a single csv field, lists are joined with `;`
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        Value::Array(values) => values.iter().map(csv_value).collect::<Vec<_>>().join(";"),
+        value => value.to_string(),
+    }
+}
+
+/// Joins fields into a RFC 4180 line, quoting fields that contain a separator, quote or line break
+fn csv_line(fields: &[String]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+
+    format!("{}\r\n", fields.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn line(fields: &[&str]) -> String {
+        csv_line(&fields.iter().map(|field| field.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn plain_fields_are_not_quoted() {
+        assert_eq!(line(&["a", "b c", ""]), "a,b c,\r\n");
+    }
+
+    #[test]
+    fn fields_with_separators_or_line_breaks_are_quoted() {
+        assert_eq!(line(&["a,b", "c\nd", "e\rf"]), "\"a,b\",\"c\nd\",\"e\rf\"\r\n");
+    }
+
+    #[test]
+    fn quotes_are_doubled() {
+        assert_eq!(line(&["say \"hi\""]), "\"say \"\"hi\"\"\"\r\n");
+    }
+
+    #[test]
+    fn lists_are_joined_with_semicolons() {
+        assert_eq!(csv_value(&json!(["a", "b"])), "a;b");
+        assert_eq!(csv_value(&Value::Null), "");
+    }
+}
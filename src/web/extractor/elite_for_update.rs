@@ -0,0 +1,20 @@
+use crate::app::error::AppError;
+use crate::model::elite::EliteForUpdate;
+use axum::Json;
+use axum::extract::{FromRequest, Request};
+use serde_json::{Map, Value};
+use tracing::trace;
+
+impl<S: Send + Sync> FromRequest<S> for EliteForUpdate {
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, AppError> {
+        trace!("{:<12} - EliteForUpdate", "EXTRACTOR");
+
+        let Json(body) = Json::<Map<String, Value>>::from_request(req, state).await.map_err(|e| AppError::BadRequest(Some(e.body_text())))?;
+
+        let elite_for_update = EliteForUpdate::try_from(body).map_err(AppError::InvalidFields)?;
+
+        Ok(elite_for_update)
+    }
+}
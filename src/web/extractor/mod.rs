@@ -0,0 +1 @@
+pub mod elite_for_update;
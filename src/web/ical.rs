@@ -0,0 +1,108 @@
+use crate::model::elite::Elite;
+use chrono::{Datelike, Utc};
+
+/// Maximum length of a content line in octets, excluding the line break
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Renders an RFC 5545 calendar with a yearly recurring all-day event for every elite with a birthday and a known ign
+pub fn birthday_calendar(elites: &[Elite]) -> String {
+    let dtstamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Elite Dashboard//Birthdays//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:Elite birthdays".to_string(),
+    ];
+
+    for elite in elites {
+        let (Some(birthday), Some(ign)) = (elite.birthday, &elite.ign) else {
+            continue;
+        };
+
+        // A plain yearly rule skips Feb 29 in common years, the last day of February covers both cases
+        let rrule = if birthday.month() == 2 && birthday.day() == 29 {
+            "RRULE:FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1"
+        } else {
+            "RRULE:FREQ=YEARLY"
+        };
+
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:birthday-{}@elite-dashboard", elite.id),
+            format!("DTSTAMP:{dtstamp}"),
+            format!("DTSTART;VALUE=DATE:{}", birthday.format("%Y%m%d")),
+            rrule.to_string(),
+            format!("SUMMARY:{}", escape_text(&format!("{ign}'s birthday"))),
+            "TRANSP:TRANSPARENT".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// Escapes a TEXT property value
+fn escape_text(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+/// Folds a content line into lines of at most 75 octets without splitting a multi-byte character and terminates it with CRLF
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut line_octets = 0;
+
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space of a continuation line counts towards its length
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_text_values() {
+        assert_eq!(escape_text(r"a\b;c,d"), r"a\\b\;c\,d");
+        assert_eq!(escape_text("line\nbreak"), r"line\nbreak");
+    }
+
+    #[test]
+    fn keeps_short_lines_unfolded() {
+        let line = "a".repeat(MAX_LINE_OCTETS);
+
+        assert_eq!(fold_line(&line), format!("{line}\r\n"));
+    }
+
+    #[test]
+    fn folds_long_lines_at_75_octets() {
+        let folded = fold_line(&"a".repeat(160));
+        let lines: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        assert_eq!(lines.iter().map(|line| line.trim_start()).collect::<String>(), "a".repeat(160));
+    }
+
+    #[test]
+    fn does_not_split_multi_byte_characters() {
+        let folded = fold_line(&"ä".repeat(40));
+
+        assert!(folded.split("\r\n").all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert_eq!(folded.replace("\r\n ", ""), format!("{}\r\n", "ä".repeat(40)));
+    }
+}
@@ -1,4 +1,70 @@
+pub mod mw_api_key_scope;
+pub mod mw_permission;
 pub mod mw_req_log;
 pub mod mw_response_map;
+pub mod mw_server_token;
 pub mod mw_session;
-pub mod mw_staff_only;
+
+use crate::app::constants::FORWARDED_FOR_HEADER;
+use crate::model::auth_event::RequestOrigin;
+use crate::web::middleware::mw_req_log::RequestId;
+use axum::extract::FromRequestParts;
+use axum::http::header::{AUTHORIZATION, USER_AGENT};
+use axum::http::request::Parts;
+use axum::http::{Extensions, HeaderMap};
+use std::convert::Infallible;
+
+/// Returns the token of an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers.get(AUTHORIZATION)?.to_str().ok()?.strip_prefix("Bearer ").map(str::trim)
+}
+
+/// Ip of the client as reported by the proxy in front of the api. The proxy appends the address it saw to `X-Forwarded-For`,
+/// so only the last entry can be trusted, everything before it is sent by the client.
+pub fn client_ip(headers: &HeaderMap) -> Option<&str> {
+    headers.get(FORWARDED_FOR_HEADER)?.to_str().ok()?.rsplit(',').next().map(str::trim).filter(|ip| !ip.is_empty())
+}
+
+pub fn user_agent(headers: &HeaderMap) -> Option<&str> {
+    headers.get(USER_AGENT)?.to_str().ok()
+}
+
+pub fn request_origin(headers: &HeaderMap, extensions: &Extensions) -> RequestOrigin {
+    RequestOrigin {
+        ip: client_ip(headers).map(str::to_string),
+        user_agent: user_agent(headers).map(str::to_string),
+        request_id: extensions.get::<RequestId>().map(|RequestId(id)| id.clone()),
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestOrigin {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Infallible> {
+        Ok(request_origin(&parts.headers, &parts.extensions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn forwarded_for(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(FORWARDED_FOR_HEADER, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn client_ip_uses_entry_appended_by_proxy() {
+        assert_eq!(client_ip(&forwarded_for("10.0.0.1, 203.0.113.7")), Some("203.0.113.7"));
+        assert_eq!(client_ip(&forwarded_for("203.0.113.7")), Some("203.0.113.7"));
+    }
+
+    #[test]
+    fn client_ip_ignores_empty_entry() {
+        assert_eq!(client_ip(&forwarded_for("10.0.0.1, ")), None);
+        assert_eq!(client_ip(&HeaderMap::new()), None);
+    }
+}
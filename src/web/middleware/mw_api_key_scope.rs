@@ -0,0 +1,34 @@
+use crate::app::error::AppError;
+use crate::model::api_key::ApiKeyScope;
+use crate::model::session::Session;
+use crate::web::error::Error::MissingApiKeyScope;
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::{debug, trace};
+
+/// Marks a request whose api key was checked against the scope of the route, api key sessions can only be extracted with it
+#[derive(Debug, Clone)]
+pub struct ApiKeyScopeGranted;
+
+/// Opens a route to api keys holding the scope given as state, e.g.
+/// `middleware::from_fn_with_state(ApiKeyScope::ElitesRead, mw_api_key_scope)`.
+/// Routes without it can not be used with an api key. Has to be the outermost route layer, so it runs before `mw_permission`.
+pub async fn mw_api_key_scope(State(scope): State<ApiKeyScope>, mut req: Request<Body>, next: Next) -> Result<Response, AppError> {
+    trace!("{:<12} - mw_api_key_scope", "MIDDLEWARE");
+
+    let Some(scopes) = req.extensions().get::<Session>().and_then(|session| session.scopes.as_ref()) else {
+        return Ok(next.run(req).await);
+    };
+
+    if !scopes.contains(&scope) {
+        debug!("{:<12} - Api key is missing scope {}", "MIDDLEWARE", scope);
+        return Err(MissingApiKeyScope.into());
+    }
+
+    req.extensions_mut().insert(ApiKeyScopeGranted);
+
+    Ok(next.run(req).await)
+}
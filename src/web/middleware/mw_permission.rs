@@ -0,0 +1,24 @@
+use crate::app::error::AppError;
+use crate::model::permission::Permission;
+use crate::model::session::Session;
+use crate::web::error::Error::MissingPermission;
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::{debug, trace};
+
+/// Only lets sessions holding the permission given as state through, e.g.
+/// `middleware::from_fn_with_state(Permission::RosterEdit, mw_permission)`
+pub async fn mw_permission(State(permission): State<Permission>, session: Session, req: Request<Body>, next: Next) -> Result<Response, AppError> {
+    trace!("{:<12} - mw_permission", "MIDDLEWARE");
+
+    // Api keys hold the permissions of their scopes, see `ApiKeyScope::permissions`
+    if !session.user.has_permission(permission) {
+        debug!("{:<12} - {} is missing permission {}", "MIDDLEWARE", session.user.id, permission);
+        return Err(MissingPermission.into());
+    }
+
+    Ok(next.run(req).await)
+}
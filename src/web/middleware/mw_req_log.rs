@@ -10,6 +10,10 @@ use strum_macros::{Display, EnumString};
 use tracing::trace;
 use uuid::Uuid;
 
+/// Id of the request, available to handlers before the [`ReqStamp`] is created
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ReqStamp {
     pub method: String,
@@ -28,7 +32,7 @@ pub enum RequestPlatform {
     Railway,
 }
 
-pub async fn mw_req_log(uri: Uri, req_method: Method, req: Request<Body>, next: Next) -> Result<Response, AppError> {
+pub async fn mw_req_log(uri: Uri, req_method: Method, mut req: Request<Body>, next: Next) -> Result<Response, AppError> {
     trace!("{:<12} - mw_req_log", "MIDDLEWARE");
 
     let req_id_header = req.headers().get(RAILWAY_REQUEST_ID_HEADER);
@@ -37,6 +41,8 @@ pub async fn mw_req_log(uri: Uri, req_method: Method, req: Request<Body>, next:
         None => (Uuid::new_v4().to_string(), RequestPlatform::Local),
     };
 
+    req.extensions_mut().insert(RequestId(req_id.clone()));
+
     let time_in = Utc::now();
 
     let mut res = next.run(req).await;
@@ -45,7 +51,8 @@ pub async fn mw_req_log(uri: Uri, req_method: Method, req: Request<Body>, next:
 
     let req_stamp = ReqStamp {
         method: req_method.to_string(),
-        uri: uri.to_string(),
+        // Only the path is kept, the query string can carry secrets such as the calendar feed token
+        uri: uri.path().to_string(),
         id: req_id.clone(),
         platform,
         time_in: time_in.to_rfc3339(),
@@ -0,0 +1,26 @@
+use crate::app::error::AppError;
+use crate::model::minecraft::MinecraftServer;
+use crate::service::MinecraftService;
+use crate::web::error::Error::InvalidServerToken;
+use crate::web::middleware::bearer_token;
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::{debug, trace};
+
+/// Authenticates game servers by the `Authorization: Bearer <token>` header and puts the server into the request extensions
+pub async fn mw_server_token(State(minecraft): State<MinecraftService>, mut req: Request<Body>, next: Next) -> Result<Response, AppError> {
+    trace!("{:<12} - mw_server_token", "MIDDLEWARE");
+
+    let token = bearer_token(req.headers()).ok_or(InvalidServerToken)?;
+
+    let server: MinecraftServer = minecraft.authenticate(token).await?.ok_or(InvalidServerToken)?;
+
+    debug!("{:<12} - Valid server token for {}", "MIDDLEWARE", server.name);
+
+    req.extensions_mut().insert(server);
+
+    Ok(next.run(req).await)
+}
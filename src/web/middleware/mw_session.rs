@@ -2,49 +2,195 @@ use axum::extract::FromRequestParts;
 use axum::{
     body::Body,
     extract::State,
-    http::{Request, request::Parts},
+    http::{Method, Request, request::Parts},
     middleware::Next,
     response::Response,
 };
+use chrono::Utc;
+use serde_json::json;
 use tower_cookies::Cookies;
-use tracing::{debug, trace};
+use tracing::{debug, info, trace, warn};
 
 use crate::web::error::Error;
 
 use crate::app::constants::{ONE_MONTH, SESSION_COOKIE_NAME};
 use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::auth_event::{AuthEventForCreate, AuthEventKind, RequestOrigin};
+use crate::model::impersonation::ImpersonationAction;
+use crate::model::permission::Permission;
 use crate::model::session::Session;
-use crate::service::SessionService;
-
-pub async fn mw_session_require(
-    cookies: Cookies,
-    State(session_store): State<SessionService>,
-    mut req: Request<Body>,
-    next: Next,
-) -> Result<Response, AppError> {
+use crate::web::auth_page::AuthFailure;
+use crate::web::middleware::mw_api_key_scope::ApiKeyScopeGranted;
+use crate::web::middleware::{bearer_token, request_origin};
+use crate::web::routes::impersonation::IMPERSONATION_PATH;
+use crate::web::routes::sessions::SESSIONS_PATH;
+
+pub async fn mw_session_require(cookies: Cookies, State(state): State<AppState>, mut req: Request<Body>, next: Next) -> Result<Response, AppError> {
     trace!("{:<12} - mw_session_require", "MIDDLEWARE");
 
+    let session_store = &state.session;
+    let origin = request_origin(req.headers(), req.extensions());
+
+    if let Some(secret) = bearer_token(req.headers()) {
+        // The scope is checked by the `mw_api_key_scope` layer of the route, routes without one reject api keys
+        let (api_key, record_use) = state.api_keys.authenticate(secret).await?.ok_or(Error::InvalidApiKey)?;
+
+        debug!("{:<12} - Valid api key {}", "MIDDLEWARE", api_key.id);
+
+        if record_use {
+            let event = AuthEventForCreate {
+                actor_id: Some(api_key.actor_id()),
+                details: Some(json!({ "api_key_id": api_key.id, "name": api_key.name, "method": req.method().as_str(), "path": req.uri().path() })),
+                ..AuthEventForCreate::new(AuthEventKind::ApiKeyUsed, Some(&api_key.created_by))
+            };
+            state.auth_events.record(event, &origin).await;
+        }
+
+        req.extensions_mut().insert(Session::for_api_key(&api_key));
+
+        return Ok(next.run(req).await);
+    }
+
     let session = cookies.get(SESSION_COOKIE_NAME).ok_or(Error::SessionCookieNotFound)?;
     let session_id = session.value().to_string();
 
     let session = session_store.validate_session(&session_id).await?;
+    let session = state.discord.auth.ensure_access_token(session_store, &session_id, session).await?;
+    let session = revalidate_role(&state, &session_id, session, &origin).await?;
+    session_store.touch_session(&session_id, &session).await?;
 
     debug!("{:<12} - Valid session", "MIDDLEWARE");
 
     // If requesting current session user refresh session and cookie ttl
     if req.uri().path() == "/elites/@me" {
         debug!("{:<12} - Refreshing session", "MIDDLEWARE");
-        session_store.refresh_session_ttl(&session_id).await?;
+        session_store.refresh_session_ttl(&session_id, &session.user.id).await?;
 
         let new_cookie = session_store.create_session_cookie(session_id, ONE_MONTH);
         cookies.add(new_cookie);
     }
 
+    let session = apply_impersonation(&state, session, req.method().clone(), req.uri().path()).await?;
+
     req.extensions_mut().insert(session.clone());
 
     Ok(next.run(req).await)
 }
 
+/// Checks the guild membership of the session user again once the role check interval passed.
+/// Users who left the guild or lost their elite roles are logged out, a changed role or permission set is applied to the session.
+/// If discord can not be reached the cached role is kept and the check is retried a minute later.
+async fn revalidate_role(state: &AppState, session_id: &str, mut session: Session, origin: &RequestOrigin) -> Result<Session, AppError> {
+    let (session_store, discord_auth) = (&state.session, &state.discord.auth);
+
+    if !session_store.is_role_check_due(&session) {
+        return Ok(session);
+    }
+
+    let member = match state.discord.api.get_elite_guild_member(&session.user.id).await {
+        Ok(member) => member,
+        Err(e) => {
+            warn!(
+                "{:<12} - Role check of {} failed, keeping cached role: {}",
+                "MIDDLEWARE", session.user.id, e
+            );
+            session_store.postpone_role_check(session_id).await?;
+            return Ok(session);
+        }
+    };
+
+    let Some(member) = member else {
+        info!("{:<12} - {} left the elite guild, invalidating session", "MIDDLEWARE", session.user.id);
+        let tokens = session_store.invalidate_session(session_id).await?;
+        discord_auth.revoke_tokens(tokens.into_iter().collect());
+        record_role_check_revocation(state, &session.user.id, AuthFailure::NotInGuild, origin).await;
+        return Err(Error::NotInEliteGuild.into());
+    };
+
+    let Some(role) = discord_auth.get_role_for_member(&member.roles) else {
+        info!("{:<12} - {} lost the elite roles, invalidating session", "MIDDLEWARE", session.user.id);
+        let tokens = session_store.invalidate_session(session_id).await?;
+        discord_auth.revoke_tokens(tokens.into_iter().collect());
+        record_role_check_revocation(state, &session.user.id, AuthFailure::NotElite, origin).await;
+        return Err(Error::NotInElite.into());
+    };
+
+    if role != session.user.role {
+        info!(
+            "{:<12} - Role of {} changed from {} to {}",
+            "MIDDLEWARE", session.user.id, session.user.role, role
+        );
+    }
+
+    let permissions = state.roles.permissions_for(&role, &member.roles).await?;
+
+    if role != session.user.role || permissions != session.user.permissions {
+        let event = AuthEventForCreate {
+            details: Some(json!({
+                "role": { "before": session.user.role, "after": role },
+                "permissions": { "before": session.user.permissions, "after": permissions },
+            })),
+            ..AuthEventForCreate::new(AuthEventKind::RoleChanged, Some(&session.user.id))
+        };
+        state.auth_events.record(event, origin).await;
+    }
+
+    session.user.permissions = permissions;
+    session.user.role = role;
+
+    session_store.save_role_check(session_id, &session.user).await?;
+
+    session.role_checked_at = Some(Utc::now());
+
+    Ok(session)
+}
+
+async fn record_role_check_revocation(state: &AppState, user_id: &str, reason: AuthFailure, origin: &RequestOrigin) {
+    let event = AuthEventForCreate {
+        reason: Some(reason.to_string()),
+        ..AuthEventForCreate::new(AuthEventKind::SessionRevoked, Some(user_id))
+    };
+    state.auth_events.record(event, origin).await;
+}
+
+/// Runs the request as the user a staff member is viewing as. Every such request is audited and only reading is allowed,
+/// apart from ending the impersonation.
+async fn apply_impersonation(state: &AppState, mut session: Session, method: Method, path: &str) -> Result<Session, AppError> {
+    // Staff that lost the permission since starting the impersonation is back to their own view
+    if session.impersonation.is_none() || !session.user.has_permission(Permission::UsersImpersonate) {
+        return Ok(session);
+    }
+
+    session.apply_impersonation();
+    let Some(impersonator) = &session.impersonator else {
+        return Ok(session);
+    };
+
+    state
+        .impersonation
+        .record(
+            &impersonator.id,
+            &session.user.id,
+            ImpersonationAction::Request,
+            Some(method.as_str()),
+            Some(path),
+        )
+        .await?;
+
+    // The session list shows the ips and user agents of the impersonated user
+    if path.starts_with(SESSIONS_PATH) {
+        return Err(Error::ImpersonationForbidden.into());
+    }
+
+    let allowed = matches!(method, Method::GET | Method::HEAD | Method::OPTIONS) || (method == Method::DELETE && path == IMPERSONATION_PATH);
+    if !allowed {
+        return Err(Error::ImpersonationReadOnly.into());
+    }
+
+    Ok(session)
+}
+
 impl<S: Send + Sync> FromRequestParts<S> for Session {
     type Rejection = AppError;
 
@@ -53,6 +199,10 @@ impl<S: Send + Sync> FromRequestParts<S> for Session {
 
         let session = parts.extensions.get::<Session>().cloned().ok_or(Error::SessionNotFound)?;
 
+        if session.is_api_key() && parts.extensions.get::<ApiKeyScopeGranted>().is_none() {
+            return Err(Error::MissingApiKeyScope.into());
+        }
+
         Ok(session)
     }
 }
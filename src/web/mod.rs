@@ -1,26 +1,42 @@
+use crate::app::constants::{NEXT_CURSOR_HEADER, TOTAL_COUNT_HEADER};
 use crate::app::state::AppState;
 use axum::Router;
-use axum::http::header::CONTENT_TYPE;
+use axum::http::header::{CONTENT_TYPE, ETAG, IF_MATCH, IF_NONE_MATCH};
 use axum::http::{Method, StatusCode};
 use axum::routing::get;
 use tower_cookies::CookieManagerLayer;
 use tower_http::cors::CorsLayer;
 
+pub mod auth_page;
 pub mod error;
+pub mod etag;
+pub mod export;
+pub mod extractor;
+pub mod ical;
 pub mod middleware;
 pub mod routes;
 
 pub fn app_router(state: AppState) -> Router {
     let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST, Method::PATCH, Method::DELETE, Method::OPTIONS])
-        .allow_origin(["http://192.168.1.38:5173".parse().unwrap()])
-        .allow_headers([CONTENT_TYPE])
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE, Method::OPTIONS])
+        .allow_origin([state.discord.auth.frontend_origin().parse().expect("Failed to parse frontend origin")])
+        .allow_headers([CONTENT_TYPE, IF_MATCH, IF_NONE_MATCH])
+        .expose_headers([ETAG, NEXT_CURSOR_HEADER.parse().unwrap(), TOTAL_COUNT_HEADER.parse().unwrap()])
         .allow_credentials(true);
 
     let authenticated_routes = Router::new()
         .merge(routes::elite::routes(state.clone()))
+        .merge(routes::api_keys::routes(state.clone()))
+        .merge(routes::audit::routes(state.clone()))
+        .merge(routes::calendar::routes(state.clone()))
         .merge(routes::discord::routes(state.clone()))
         .merge(routes::ign_history::routes(state.clone()))
+        .merge(routes::impersonation::routes(state.clone()))
+        .merge(routes::minecraft::routes(state.clone()))
+        .merge(routes::roles::routes(state.clone()))
+        .merge(routes::sessions::routes(state.clone()))
+        .merge(routes::skin_history::routes(state.clone()))
+        .merge(routes::tracked_uuids::routes(state.clone()))
         .nest("/dashboard", routes::dashboard::routes(state.clone()))
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
@@ -30,6 +46,9 @@ pub fn app_router(state: AppState) -> Router {
     Router::new()
         .merge(authenticated_routes)
         .merge(routes::auth::routes(state.clone()))
+        .merge(routes::avatar::routes(state.clone()))
+        .merge(routes::calendar::feed_routes(state.clone()))
+        .merge(routes::minecraft::server_routes(state.clone()))
         .route("/healthz", get(|| async { StatusCode::OK }))
         .layer(axum::middleware::from_fn(middleware::mw_req_log::mw_req_log))
         .layer(axum::middleware::map_response(middleware::mw_response_map::mw_response_map))
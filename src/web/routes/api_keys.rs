@@ -0,0 +1,62 @@
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::api_key::{ApiKey, ApiKeyForCreate};
+use crate::model::permission::Permission;
+use crate::model::session::Session;
+use crate::service::ApiKeyService;
+use crate::web::error::Error;
+use crate::web::middleware::mw_permission::mw_permission;
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get};
+use axum::{Router, middleware};
+use serde_json::{Value, json};
+use tracing::debug;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route(
+            "/api-keys",
+            get(api_keys).post(create_api_key).layer(middleware::from_fn_with_state(Permission::ApiKeysManage, mw_permission)),
+        )
+        .route(
+            "/api-keys/{key_id}",
+            delete(revoke_api_key).layer(middleware::from_fn_with_state(Permission::ApiKeysManage, mw_permission)),
+        )
+        .with_state(state)
+}
+
+async fn api_keys(State(api_keys): State<ApiKeyService>) -> Result<Json<Vec<ApiKey>>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /api-keys");
+
+    let keys = api_keys.keys_all().await?;
+
+    Ok(Json(keys))
+}
+
+/// Mints a key for the bot role, the response contains the secret which is not shown again
+async fn create_api_key(
+    session: Session,
+    State(api_keys): State<ApiKeyService>,
+    Json(mut new_key): Json<ApiKeyForCreate>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    debug!("{:<12} - {}", "HANDLER", "POST /api-keys");
+
+    new_key.validate().map_err(AppError::InvalidFields)?;
+
+    if let Some(scope) = new_key.scopes.iter().find(|scope| !scope.is_grantable_by(&session.user)) {
+        return Err(Error::ApiKeyScopeNotGrantable(*scope).into());
+    }
+
+    let (key, secret) = api_keys.create_key(&new_key, &session.user.id).await?;
+
+    Ok((StatusCode::CREATED, Json(json!({ "api_key": key, "secret": secret }))))
+}
+
+async fn revoke_api_key(State(api_keys): State<ApiKeyService>, Path(key_id): Path<i32>) -> Result<StatusCode, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "DELETE /api-keys/", key_id);
+
+    api_keys.revoke_key(key_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
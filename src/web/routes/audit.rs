@@ -0,0 +1,89 @@
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::auth_event::{AuthEvent, AuthEventFilter, AuthEventKind};
+use crate::model::elite::EliteChange;
+use crate::model::impersonation::ImpersonationEvent;
+use crate::model::permission::Permission;
+use crate::service::{AuthEventService, EliteService, ImpersonationService};
+use crate::web::middleware::mw_permission::mw_permission;
+use crate::web::routes::OffsetQueryParams;
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Json, Router, middleware};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::debug;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route(
+            "/audit/elites",
+            get(audit_elites).layer(middleware::from_fn_with_state(Permission::AuditRead, mw_permission)),
+        )
+        .route(
+            "/audit/auth",
+            get(audit_auth).layer(middleware::from_fn_with_state(Permission::AuditRead, mw_permission)),
+        )
+        .route(
+            "/audit/impersonations",
+            get(audit_impersonations).layer(middleware::from_fn_with_state(Permission::AuditRead, mw_permission)),
+        )
+        .with_state(state)
+}
+
+async fn audit_elites(State(elite): State<EliteService>, Query(params): Query<OffsetQueryParams>) -> Result<Json<Vec<EliteChange>>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /audit/elites");
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let changes = elite.changes_all(limit, offset).await?;
+
+    Ok(Json(changes))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthAuditQueryParams {
+    event: Option<AuthEventKind>,
+    discord_user_id: Option<String>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+/// Logins, logouts, revocations, role changes and api key use, filterable by event, user and time range
+async fn audit_auth(
+    State(auth_events): State<AuthEventService>,
+    Query(params): Query<AuthAuditQueryParams>,
+) -> Result<Json<Vec<AuthEvent>>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /audit/auth");
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let filter = AuthEventFilter {
+        event: params.event,
+        discord_user_id: params.discord_user_id,
+        since: params.since,
+        until: params.until,
+    };
+
+    let events = auth_events.events(&filter, limit, offset).await?;
+
+    Ok(Json(events))
+}
+
+async fn audit_impersonations(
+    State(impersonation): State<ImpersonationService>,
+    Query(params): Query<OffsetQueryParams>,
+) -> Result<Json<Vec<ImpersonationEvent>>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /audit/impersonations");
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let events = impersonation.events(limit, offset).await?;
+
+    Ok(Json(events))
+}
@@ -1,19 +1,22 @@
 use axum::extract::{Query, State};
+use axum::http::HeaderMap;
 use axum::response::Html;
 use axum::routing::{delete, get};
 use axum::{Json, Router};
 use oauth2::{CsrfToken, TokenResponse};
 use serde::Deserialize;
 use serde_json::{Value, json};
-use tokio::fs;
 use tower_cookies::Cookies;
 use tracing::{debug, warn};
 
 use crate::AppState;
 use crate::app::constants::{FIVE_MINUTES, ONE_MONTH, SESSION_COOKIE_NAME};
-use crate::app::error::Result;
-use crate::service::{DiscordApiService, DiscordAuthService, SessionService};
-use crate::web::error::Error;
+use crate::app::error::{AppError, Result};
+use crate::model::auth_event::{AuthEventForCreate, AuthEventKind, RequestOrigin};
+use crate::model::session::SessionUser;
+use crate::service::{AuthEventService, DiscordAuthService, SessionService};
+use crate::web::auth_page::{AuthFailure, auth_callback_page};
+use crate::web::middleware::{client_ip, user_agent};
 
 pub fn routes(state: AppState) -> Router {
     Router::new()
@@ -29,9 +32,9 @@ pub async fn auth_discord(
     cookies: Cookies,
 ) -> Result<Json<Value>> {
     debug!("{:<12} - {}", "HANDLER", "auth_discord");
-    let (auth_url, csrf_token) = discord_auth.init_auth();
+    let (auth_url, csrf_token, pkce_verifier) = discord_auth.init_auth();
 
-    let session_id = session.init_session(&csrf_token).await?;
+    let session_id = session.init_session(&csrf_token, &pkce_verifier).await?;
 
     // Create and set session cookie
     // User has to complete initial auth flow within 5 minutes. When auth flow succeeds, session cookie expiration will be increased
@@ -44,7 +47,13 @@ pub async fn auth_discord(
     })))
 }
 
-pub async fn auth_logout(State(session_store): State<SessionService>, cookies: Cookies) -> Result<()> {
+pub async fn auth_logout(
+    State(session_store): State<SessionService>,
+    State(discord_auth): State<DiscordAuthService>,
+    State(auth_events): State<AuthEventService>,
+    origin: RequestOrigin,
+    cookies: Cookies,
+) -> Result<()> {
     debug!("{:<12} - {}", "HANDLER", "auth_logout");
 
     let session_cookie = cookies.get(SESSION_COOKIE_NAME);
@@ -52,7 +61,15 @@ pub async fn auth_logout(State(session_store): State<SessionService>, cookies: C
     if let Some(session_cookie) = session_cookie {
         let session_id = session_cookie.value().to_string();
 
-        session_store.invalidate_session(&session_id).await?;
+        // Pre login sessions have no user, their removal is not worth an audit entry
+        let user_id = session_store.get_session_by_id(&session_id).await.ok().flatten().map(|session| session.user.id);
+
+        let tokens = session_store.invalidate_session(&session_id).await?;
+        discord_auth.revoke_tokens(tokens.into_iter().collect());
+
+        if let Some(user_id) = user_id {
+            auth_events.record(AuthEventForCreate::new(AuthEventKind::Logout, Some(&user_id)), &origin).await;
+        }
 
         cookies.remove(session_store.create_session_cookie(session_id, 0));
     }
@@ -69,87 +86,123 @@ pub struct DiscordCallbackQueryParams {
 }
 
 pub async fn auth_discord_callback(
-    State(session_store): State<SessionService>,
-    State(discord_auth): State<DiscordAuthService>,
-    State(discord_api): State<DiscordApiService>,
+    State(state): State<AppState>,
     Query(params): Query<DiscordCallbackQueryParams>,
+    origin: RequestOrigin,
+    headers: HeaderMap,
     cookies: Cookies,
-) -> Result<Html<String>> {
+) -> Html<String> {
     debug!("{:<12} - {}", "HANDLER", "auth_discord_callback");
 
+    let mut user_id = None;
+    let result = complete_login(&state, params, &headers, &cookies, &mut user_id).await;
+
+    let event = match result {
+        Ok(()) => AuthEventForCreate::new(AuthEventKind::LoginSuccess, user_id.as_deref()),
+        Err(reason) => {
+            warn!("{:<12} - Discord Oauth flow failed: {}", "HANDLER", reason);
+
+            AuthEventForCreate {
+                reason: Some(reason.to_string()),
+                ..AuthEventForCreate::new(AuthEventKind::LoginFailure, user_id.as_deref())
+            }
+        }
+    };
+    state.auth_events.record(event, &origin).await;
+
+    Html(auth_callback_page(state.discord.auth.frontend_origin(), result))
+}
+
+/// Runs the login steps after discord redirected back. `user_id` is set as soon as discord identified the user,
+/// so failed logins of known users can be attributed to them.
+async fn complete_login(
+    state: &AppState,
+    params: DiscordCallbackQueryParams,
+    headers: &HeaderMap,
+    cookies: &Cookies,
+    user_id: &mut Option<String>,
+) -> std::result::Result<(), AuthFailure> {
+    let (session_store, discord_auth, discord_api) = (&state.session, &state.discord.auth, &state.discord.api);
+
     // Check for error
     if params.error.is_some() || params.error_description.is_some() {
-        return Ok(handle_callback_error(&params.error, &params.error_description));
+        warn!(
+            "{:<12} - Discord returned an error: {:?} - Description: {:?}",
+            "HANDLER", params.error, params.error_description
+        );
+
+        return match params.error.as_deref() {
+            Some("access_denied") => Err(AuthFailure::AccessDenied),
+            _ => Err(AuthFailure::DiscordError),
+        };
     }
 
     // code and state are required in callback
-    let code = params.code.ok_or(Error::NoCodeInDiscordCallbackPath)?;
-    let state = params.state.ok_or(Error::NoStateInDiscordCallbackPath)?;
+    let code = params.code.ok_or(AuthFailure::InvalidState)?;
+    let csrf_state = params.state.ok_or(AuthFailure::InvalidState)?;
 
-    let session_cookie = cookies.get(SESSION_COOKIE_NAME).ok_or(Error::SessionCookieNotFound)?;
+    let session_cookie = cookies.get(SESSION_COOKIE_NAME).ok_or(AuthFailure::InvalidState)?;
     let session_id = session_cookie.value().to_string();
 
-    debug!("{:<12} - Session ID: {:?}", "HANDLER", &session_id);
-
     // Check valid session by session_id and state (csrf_token)
-    session_store.validate_init_session(&session_id, &CsrfToken::new(state.clone())).await?;
+    let pkce_verifier = session_store
+        .validate_init_session(&session_id, &CsrfToken::new(csrf_state))
+        .await
+        .map_err(|e| auth_failure(e, AuthFailure::InvalidState))?;
 
     debug!("{:<12} - Validated session", "HANDLER");
 
-    let tokens = discord_auth.exchanged_code_for_tokens(&code).await?;
+    let tokens = discord_auth
+        .exchanged_code_for_tokens(&code, pkce_verifier)
+        .await
+        .map_err(|e| auth_failure(e, AuthFailure::DiscordError))?;
 
     debug!("{:<12} - Exchanged code for tokens", "HANDLER");
 
-    let self_user_id = discord_auth.get_discord_self_user_id(tokens.access_token()).await?;
-
-    let elite_member = discord_api.get_elite_guild_member(&self_user_id).await?.ok_or(Error::NotInEliteGuild)?;
-
-    let user_role = discord_auth.get_role_for_member(&elite_member.roles).ok_or(Error::NotInElite)?;
-
-    session_store.save_session(&session_id, &tokens, &self_user_id, &user_role).await?;
-
-    // Discord oauth flow successful. Session cookie valid for 1 month
-    let session_cookie = session_store.create_session_cookie(session_id.clone(), ONE_MONTH);
+    let self_user_id = discord_auth
+        .get_discord_self_user_id(tokens.access_token())
+        .await
+        .map_err(|e| auth_failure(e.into(), AuthFailure::DiscordError))?;
+    *user_id = Some(self_user_id.clone());
+
+    let elite_member = discord_api
+        .get_elite_guild_member(&self_user_id)
+        .await
+        .map_err(|e| auth_failure(e.into(), AuthFailure::DiscordError))?
+        .ok_or(AuthFailure::NotInGuild)?;
+
+    let user_role = discord_auth.get_role_for_member(&elite_member.roles).ok_or(AuthFailure::NotElite)?;
+
+    let permissions = state
+        .roles
+        .permissions_for(&user_role, &elite_member.roles)
+        .await
+        .map_err(|e| auth_failure(e, AuthFailure::ServerError))?;
+
+    let user = SessionUser {
+        id: self_user_id,
+        role: user_role,
+        permissions,
+    };
+
+    let session_id = session_store
+        .save_session(&session_id, &tokens, &user, client_ip(headers), user_agent(headers))
+        .await
+        .map_err(|e| auth_failure(e, AuthFailure::ServerError))?;
+
+    // Discord oauth flow successful. The cookie gets the rotated session id and is valid for 1 month
+    let session_cookie = session_store.create_session_cookie(session_id, ONE_MONTH);
     cookies.add(session_cookie);
 
-    // TODO: Properly implement response html with error state
-    // Return the appropriate HTML response
-    render_callback_response().await
-}
-
-fn handle_callback_error(error: &Option<String>, error_description: &Option<String>) -> Html<String> {
-    warn!(
-        "{:<12} - Discord Oauth flow failed: Error: {:?} - Description: {:?}",
-        "HANDLER", error, error_description
-    );
-    // Html(DISCORD_AUTH_FAILED_HTML)
-    todo!()
+    Ok(())
 }
 
-async fn render_callback_response() -> Result<Html<String>> {
-    let fallback = generate_fallback_html();
+/// Reason shown to the user for an error of a login step, errors of the server itself are not blamed on the step
+fn auth_failure(error: AppError, failure: AuthFailure) -> AuthFailure {
+    debug!("{:<12} - Login step failed: {:?}", "HANDLER", error);
 
-    match fs::read_to_string("../html/auth-redirect.html").await {
-        Ok(contents) => Ok(Html(contents)),
-        Err(err) => {
-            debug!("Failed to read 'auth-redirect.html': {}", err);
-            Ok(Html(fallback))
-        }
+    match error {
+        AppError::InternalServerError => AuthFailure::ServerError,
+        _ => failure,
     }
 }
-
-fn generate_fallback_html() -> String {
-    r#"
-        <html>
-            <body>
-                <script>
-                    window.opener?.postMessage({ type: "discordAuthComplete" }, "*");
-                    window.close();
-                    window.history.replaceState({}, document.title, '/auth/discord/callback');
-                </script>
-                <p>You can close this window.</p>
-            </body>
-        </html>
-    "#
-    .to_string()
-}
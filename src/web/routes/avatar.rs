@@ -0,0 +1,53 @@
+use crate::app::constants::AVATAR_SIZES;
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::service::{AvatarService, IgnTrackerService};
+use crate::web::error::Error;
+use axum::Router;
+use axum::extract::{Path, Query, State};
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use serde::Deserialize;
+use tracing::debug;
+use uuid::Uuid;
+
+const DEFAULT_AVATAR_SIZE: u32 = 64;
+
+pub fn routes(state: AppState) -> Router {
+    // axum's router does not support suffixes after path parameters, so `.png` is stripped in the handler
+    Router::new().route("/avatars/{file}", get(avatar)).with_state(state)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AvatarQueryParams {
+    size: Option<u32>,
+}
+
+async fn avatar(
+    State(ign_tracker): State<IgnTrackerService>,
+    State(avatars): State<AvatarService>,
+    Path(file): Path<String>,
+    Query(params): Query<AvatarQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "GET /avatars/", file);
+
+    let uuid = file
+        .strip_suffix(".png")
+        .and_then(|uuid| Uuid::parse_str(uuid).ok())
+        .ok_or_else(|| Error::AvatarNotFound(format!("Avatar {file} does not exist.")))?;
+
+    let size = params.size.unwrap_or(DEFAULT_AVATAR_SIZE);
+    if !AVATAR_SIZES.contains(&size) {
+        return Err(Error::InvalidAvatarSize(size).into());
+    }
+
+    let texture_id = ign_tracker
+        .get_latest_skin_texture_id(&uuid)
+        .await?
+        .ok_or_else(|| Error::AvatarNotFound(format!("No skin recorded for tracked uuid {uuid}.")))?;
+
+    let avatar = avatars.get_avatar(&texture_id, size).await?;
+
+    Ok(([(CONTENT_TYPE, "image/png"), (CACHE_CONTROL, "public, max-age=3600")], avatar))
+}
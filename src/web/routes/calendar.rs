@@ -0,0 +1,61 @@
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::session::Session;
+use crate::service::{CalendarService, EliteService};
+use crate::web::error::Error;
+use crate::web::ical::birthday_calendar;
+use axum::extract::{Query, State};
+use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tracing::debug;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new().route("/calendar/token", post(rotate_token)).with_state(state)
+}
+
+/// Calendar apps can not send the session cookie, so the feed is authenticated by a per-user token instead
+pub fn feed_routes(state: AppState) -> Router {
+    Router::new().route("/calendar/birthdays.ics", get(birthdays_feed)).with_state(state)
+}
+
+/// Creates a new feed token for the current user, invalidating the previous one
+async fn rotate_token(session: Session, State(calendar): State<CalendarService>) -> Result<Json<Value>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "POST /calendar/token");
+
+    let token = calendar.rotate_token(&session.user.id).await?;
+
+    Ok(Json(json!({
+        "token": token,
+        "feed_path": format!("/calendar/birthdays.ics?token={token}"),
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct FeedQueryParams {
+    token: Option<String>,
+}
+
+async fn birthdays_feed(
+    State(calendar): State<CalendarService>,
+    State(elite): State<EliteService>,
+    Query(params): Query<FeedQueryParams>,
+) -> Result<impl IntoResponse, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /calendar/birthdays.ics");
+
+    let token = params.token.ok_or(Error::InvalidCalendarToken)?;
+    let discord_user_id = calendar.find_user_by_token(&token).await?.ok_or(Error::InvalidCalendarToken)?;
+
+    // Tokens of members that left the elite stop working without having to be revoked
+    elite.find_by_discord_id(&discord_user_id).await?.ok_or(Error::InvalidCalendarToken)?;
+
+    let elites = elite.birthdays_all().await?;
+
+    Ok((
+        [(CONTENT_TYPE, "text/calendar; charset=utf-8"), (CACHE_CONTROL, "private, max-age=3600")],
+        birthday_calendar(&elites),
+    ))
+}
@@ -1,7 +1,8 @@
 use crate::app::error::AppError;
 use crate::app::state::AppState;
 use crate::model::elite::{Elite, EliteStatus};
-use crate::model::recent_change::RecentChange;
+use crate::model::recent_change::{RecentChange, RecentChangesFilter};
+use crate::model::session::Session;
 use crate::service::{EliteService, IgnTrackerService};
 use axum::extract::State;
 use axum::routing::get;
@@ -15,7 +16,7 @@ pub fn routes(state: AppState) -> Router {
         .with_state(state)
 }
 
-pub async fn dashboard_elites(State(elites): State<EliteService>) -> Result<Json<Value>, AppError> {
+pub async fn dashboard_elites(_session: Session, State(elites): State<EliteService>) -> Result<Json<Value>, AppError> {
     let elites = elites.elites_all(&vec![EliteStatus::Staff, EliteStatus::Veteran, EliteStatus::Elite, EliteStatus::Trial]).await?;
 
     let elite_count = elites.len();
@@ -29,8 +30,8 @@ pub async fn dashboard_elites(State(elites): State<EliteService>) -> Result<Json
     })))
 }
 
-pub async fn dashboard_ign_tracker(State(ign_tracker): State<IgnTrackerService>) -> Result<Json<Vec<RecentChange>>, AppError> {
-    let latest_changes = ign_tracker.get_latest_changes(7, 0).await?;
+pub async fn dashboard_ign_tracker(_session: Session, State(ign_tracker): State<IgnTrackerService>) -> Result<Json<Vec<RecentChange>>, AppError> {
+    let latest_changes = ign_tracker.get_latest_changes(&RecentChangesFilter::default(), 7, 0).await?;
 
     Ok(Json(latest_changes))
 }
@@ -1,5 +1,6 @@
 use crate::app::error::Result;
 use crate::app::state::{AppState, DiscordState};
+use crate::model::session::Session;
 use crate::web::error::Error;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
@@ -16,14 +17,14 @@ pub fn routes(state: AppState) -> Router {
         .with_state(state)
 }
 
-pub async fn elite_guild(State(discord): State<DiscordState>) -> Result<impl IntoResponse> {
+pub async fn elite_guild(_session: Session, State(discord): State<DiscordState>) -> Result<impl IntoResponse> {
     debug!("{:<12} - {}", "HANDLER", "discord_guild_elite");
 
     let guild = discord.api.get_elite_guild().await.map_err(|e| Error::DiscordApiError(e.to_string()))?;
     Ok((StatusCode::OK, Json(json!({"guild": guild}))).into_response())
 }
 
-pub async fn elite_member(State(discord): State<DiscordState>, Path(user_id): Path<String>) -> Result<impl IntoResponse> {
+pub async fn elite_member(_session: Session, State(discord): State<DiscordState>, Path(user_id): Path<String>) -> Result<impl IntoResponse> {
     debug!("{:<12} - {}", "HANDLER", "discord_member_elite");
 
     let member = discord.api.get_elite_guild_member(&user_id).await.map_err(|e| Error::DiscordApiError(e.to_string()))?;
@@ -1,22 +1,70 @@
+use crate::app::constants::{NEXT_CURSOR_HEADER, TOTAL_COUNT_HEADER};
 use crate::app::error::AppError;
 use crate::app::state::AppState;
-use crate::model::elite::{Elite, EliteForUpdate, EliteStatus};
+use crate::model::api_key::ApiKeyScope;
+use crate::model::elite::{
+    ELITE_EXPORT_COLUMNS, Elite, EliteChange, EliteForCreate, EliteForUpdate, EliteRosterCursor, EliteRosterFilter, EliteSort, EliteStatus,
+    ExportFormat, ImportConflict, ImportReport, NAME_HISTORY_COLUMN, SortOrder, UpcomingBirthday, parse_import_csv,
+};
+use crate::model::mojang::is_valid_ign;
+use crate::model::permission::Permission;
 use crate::model::session::Session;
-use crate::service::EliteService;
+use crate::service::{DiscordApiService, EliteService, MojangApiService};
 use crate::web::error::Error;
-use crate::web::middleware::mw_staff_only::mw_staff_only;
+use crate::web::etag::{content_etag, if_match_versions, if_none_match, version_etag};
+use crate::web::export::ExportWriter;
+use crate::web::middleware::mw_api_key_scope::mw_api_key_scope;
+use crate::web::middleware::mw_permission::mw_permission;
+use crate::web::routes::OffsetQueryParams;
+use axum::body::Body;
 use axum::extract::{Json, Path, Query, State};
-use axum::routing::{get, patch};
+use axum::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE, ETAG};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, patch, post};
 use axum::{Router, middleware};
+use chrono::Utc;
+use futures_util::StreamExt;
+use futures_util::stream;
 use serde::Deserialize;
 use serde_json::{Value, json};
 use tracing::debug;
 
+const DEFAULT_PAGE_LIMIT: i64 = 100;
+const MAX_PAGE_LIMIT: i64 = 500;
+const DEFAULT_BIRTHDAY_DAYS: i64 = 30;
+const MAX_BIRTHDAY_DAYS: i64 = 366;
+
 pub fn routes(state: AppState) -> Router {
     Router::new()
         .route("/elites/@me", get(elites_me))
-        .route("/elites", get(elites))
-        .route("/elites/{elite_id}", patch(patch_elite).layer(middleware::from_fn(mw_staff_only)))
+        .route("/elites/birthdays/upcoming", get(upcoming_birthdays))
+        .route(
+            "/elites/export",
+            get(export_elites).layer(middleware::from_fn_with_state(Permission::RosterExport, mw_permission)),
+        )
+        .route(
+            "/elites/import",
+            post(import_elites).layer(middleware::from_fn_with_state(Permission::RosterEdit, mw_permission)),
+        )
+        .route(
+            "/elites",
+            get(elites).layer(middleware::from_fn_with_state(ApiKeyScope::ElitesRead, mw_api_key_scope)).merge(
+                post(create_elite)
+                    .layer(middleware::from_fn_with_state(Permission::RosterEdit, mw_permission))
+                    .layer(middleware::from_fn_with_state(ApiKeyScope::ElitesWrite, mw_api_key_scope)),
+            ),
+        )
+        .route(
+            "/elites/{elite_id}",
+            patch(patch_elite)
+                .layer(middleware::from_fn_with_state(Permission::RosterEdit, mw_permission))
+                .layer(middleware::from_fn_with_state(ApiKeyScope::ElitesWrite, mw_api_key_scope)),
+        )
+        .route(
+            "/elites/{elite_id}/changes",
+            get(elite_changes).layer(middleware::from_fn_with_state(Permission::AuditRead, mw_permission)),
+        )
         .with_state(state)
 }
 
@@ -29,6 +77,9 @@ async fn elites_me(session: Session, State(elite): State<EliteService>) -> Resul
     Ok(Json(json!({
         "ign": elite.ign,
         "role": session.user.role.to_string(),
+        "permissions": session.user.permissions,
+        // Shown as a banner while staff is viewing the dashboard as this user
+        "impersonation": session.impersonator,
     })))
 }
 
@@ -36,32 +87,323 @@ async fn elites_me(session: Session, State(elite): State<EliteService>) -> Resul
 #[allow(non_snake_case)]
 struct ElitesQueryParams {
     includeExElites: Option<bool>,
+    /// Case insensitive prefix of a current or past ign
+    search: Option<String>,
+    country: Option<String>,
+    /// Comma separated list of statuses, takes precedence over `includeExElites`
+    status: Option<String>,
+    being_tracked: Option<bool>,
+    sort: Option<EliteSort>,
+    order: Option<SortOrder>,
+    limit: Option<i64>,
+    cursor: Option<String>,
 }
 
-async fn elites(session: Session, State(elite): State<EliteService>, Query(params): Query<ElitesQueryParams>) -> Result<Json<Vec<Elite>>, AppError> {
+/// Returns the matching elites as an array. Without `limit` and `cursor` every elite is returned, otherwise a single page.
+/// The number of matching elites is returned in the `X-Total-Count` header, the cursor of the next page in `X-Next-Cursor`.
+async fn elites(
+    session: Session,
+    State(elite): State<EliteService>,
+    Query(params): Query<ElitesQueryParams>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
     let include_ex_elites = params.includeExElites.unwrap_or(false);
     debug!("{:<12} - {} | includeExElites={}", "HANDLER", "GET /elites", include_ex_elites);
 
-    let statuses = if include_ex_elites && session.user.is_staff() {
+    let limit = match (params.limit, &params.cursor) {
+        (Some(limit), _) => Some(limit.clamp(1, MAX_PAGE_LIMIT)),
+        (None, Some(_)) => Some(DEFAULT_PAGE_LIMIT),
+        (None, None) => None,
+    };
+
+    let mut statuses = match params.status {
+        Some(statuses) => statuses
+            .split(',')
+            .map(|status| status.trim().parse::<EliteStatus>().map_err(|_| Error::InvalidEliteStatus(status.trim().to_string())))
+            .collect::<Result<Vec<_>, _>>()?,
+        None if include_ex_elites => vec![EliteStatus::Staff, EliteStatus::Veteran, EliteStatus::Elite, EliteStatus::Trial, EliteStatus::None],
+        None => vec![EliteStatus::Staff, EliteStatus::Veteran, EliteStatus::Elite, EliteStatus::Trial],
+    };
+
+    // Ex elites are only visible with the permission to see them
+    if !session.user.has_permission(Permission::RosterReadExElites) {
+        statuses.retain(|status| *status != EliteStatus::None);
+    }
+
+    let sort = params.sort.unwrap_or_default();
+    let order = params.order.unwrap_or_default();
+
+    let cursor = match params.cursor {
+        Some(cursor) => {
+            let cursor = EliteRosterCursor::decode(&cursor).ok_or(Error::InvalidCursor)?;
+            if cursor.sort != sort || cursor.order != order {
+                return Err(Error::InvalidCursor.into());
+            }
+            Some(cursor)
+        }
+        None => None,
+    };
+
+    let filter = EliteRosterFilter {
+        statuses,
+        search: params.search.map(|search| search.trim().to_string()).filter(|search| !search.is_empty()),
+        country_code: params.country.map(|country| country.trim().to_uppercase()),
+        being_tracked: params.being_tracked,
+        sort,
+        order,
+        cursor,
+    };
+
+    let total = elite.count_elites(&filter).await?;
+
+    // Fetch one additional elite to know whether there is a next page
+    let mut elites = elite.search_elites(&filter, limit.map(|limit| limit + 1)).await?;
+
+    let next_cursor = match limit {
+        Some(limit) if elites.len() as i64 > limit => {
+            elites.truncate(limit as usize);
+            elites.last().map(|last| EliteRosterCursor::after(last, sort, order).encode())
+        }
+        _ => None,
+    };
+
+    let body = serde_json::to_vec(&elites).map_err(|_| AppError::InternalServerError)?;
+    let etag = content_etag(&body);
+
+    if if_none_match(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response());
+    }
+
+    let mut response = ([(CONTENT_TYPE, HeaderValue::from_static("application/json")), (ETAG, etag)], body).into_response();
+    response.headers_mut().insert(TOTAL_COUNT_HEADER, HeaderValue::from(total));
+    if let Some(next_cursor) = next_cursor {
+        let next_cursor = HeaderValue::from_str(&next_cursor).map_err(|_| AppError::InternalServerError)?;
+        response.headers_mut().insert(NEXT_CURSOR_HEADER, next_cursor);
+    }
+
+    Ok(response)
+}
+
+async fn create_elite(
+    session: Session,
+    State(elite): State<EliteService>,
+    State(discord_api): State<DiscordApiService>,
+    State(mojang): State<MojangApiService>,
+    Json(mut new_elite): Json<EliteForCreate>,
+) -> Result<Response, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "POST /elites");
+    debug!("{:?}", new_elite);
+
+    new_elite.validate().map_err(AppError::InvalidFields)?;
+
+    discord_api
+        .get_elite_guild_member(&new_elite.discord_user_id)
+        .await?
+        .ok_or_else(|| Error::DiscordUserNotInEliteGuild(new_elite.discord_user_id.clone()))?;
+
+    let profile = match (&new_elite.minecraft_uuid, &new_elite.ign) {
+        (Some(uuid), None) => mojang.get_profile(uuid).await?.ok_or_else(|| Error::PlayerNotFound(format!("No player with uuid {uuid} exists.")))?,
+        (None, Some(ign)) => {
+            if !is_valid_ign(ign) {
+                return Err(Error::InvalidIgn(ign.clone()).into());
+            }
+            mojang
+                .get_profile_by_name(ign)
+                .await?
+                .ok_or_else(|| Error::PlayerNotFound(format!("No player with ign {ign} exists.")))?
+        }
+        _ => return Err(Error::InvalidEliteForCreate.into()),
+    };
+
+    let created_elite = elite.create_elite(&new_elite, &profile.id, &profile.name, &session.user.id).await?;
+
+    Ok((StatusCode::CREATED, [(ETAG, version_etag(created_elite.version))], Json(created_elite)).into_response())
+}
+
+async fn patch_elite(
+    session: Session,
+    Path(elite_id): Path<i32>,
+    State(elite): State<EliteService>,
+    headers: HeaderMap,
+    updated_elite: EliteForUpdate,
+) -> Result<Response, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "PATCH /elites/", elite_id);
+    debug!("{:?}", updated_elite);
+
+    let expected_versions = if_match_versions(&headers);
+    let updated_elite = elite.update_elite(elite_id, &updated_elite, &session.user.id, expected_versions.as_deref()).await?;
+
+    match updated_elite {
+        Some(updated_elite) => Ok(([(ETAG, version_etag(updated_elite.version))], Json(Some(updated_elite))).into_response()),
+        None => Ok(Json(None::<Elite>).into_response()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(non_snake_case)]
+struct ExportQueryParams {
+    format: Option<ExportFormat>,
+    /// Comma separated list of columns, defaults to all columns
+    columns: Option<String>,
+    includeExElites: Option<bool>,
+    includeNameHistory: Option<bool>,
+}
+
+/// Streams the roster as a file download, rows are serialized as they are read from the database
+async fn export_elites(State(elite): State<EliteService>, Query(params): Query<ExportQueryParams>) -> Result<Response, AppError> {
+    let format = params.format.unwrap_or_default();
+    let include_ex_elites = params.includeExElites.unwrap_or(false);
+    let include_name_history = params.includeNameHistory.unwrap_or(false);
+    debug!("{:<12} - {} | format={:?}", "HANDLER", "GET /elites/export", format);
+
+    let mut columns = match params.columns {
+        Some(columns) => {
+            let columns: Vec<String> = columns.split(',').map(|column| column.trim().to_string()).collect();
+            if let Some(unknown) = columns.iter().find(|column| !ELITE_EXPORT_COLUMNS.contains(&column.as_str())) {
+                return Err(Error::InvalidExportColumn(unknown.clone()).into());
+            }
+            columns
+        }
+        None => ELITE_EXPORT_COLUMNS.iter().map(|column| column.to_string()).collect(),
+    };
+    if include_name_history {
+        columns.push(NAME_HISTORY_COLUMN.to_string());
+    }
+
+    let statuses = if include_ex_elites {
         vec![EliteStatus::Staff, EliteStatus::Veteran, EliteStatus::Elite, EliteStatus::Trial, EliteStatus::None]
     } else {
         vec![EliteStatus::Staff, EliteStatus::Veteran, EliteStatus::Elite, EliteStatus::Trial]
     };
 
+    let rows = elite.elites_export(&statuses, include_name_history).await?;
+
+    let mut writer = ExportWriter::new(format, columns);
+    let header = writer.header();
+
+    let body = stream::once(async move { Ok(header) })
+        .chain(rows.map(move |row| row.map(|row| writer.row(&row))))
+        .chain(stream::once(async move { Ok(ExportWriter::footer(format)) }))
+        .map(|chunk| chunk.map_err(|e| std::io::Error::other(format!("{e:?}"))));
+
+    let filename = format!("elites-{}.{}", Utc::now().format("%Y-%m-%d"), format.extension());
+
+    Ok((
+        [
+            (CONTENT_TYPE, HeaderValue::from_static(format.content_type())),
+            (
+                CONTENT_DISPOSITION,
+                HeaderValue::from_str(&format!("attachment; filename=\"{filename}\"")).map_err(|_| AppError::InternalServerError)?,
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportQueryParams {
+    dry_run: Option<bool>,
+}
+
+/// Imports a csv of elites, rows are matched to existing elites by uuid or discord user id.
+/// A dry run only returns the diff, otherwise everything is applied in one transaction or nothing if any row conflicts.
+async fn import_elites(
+    session: Session,
+    State(elite): State<EliteService>,
+    State(discord_api): State<DiscordApiService>,
+    State(mojang): State<MojangApiService>,
+    Query(params): Query<ImportQueryParams>,
+    body: String,
+) -> Result<(StatusCode, Json<ImportReport>), AppError> {
+    let dry_run = params.dry_run.unwrap_or(false);
+    debug!("{:<12} - {} | dry_run={}", "HANDLER", "POST /elites/import", dry_run);
+
+    let rows = parse_import_csv(&body).map_err(Error::InvalidImportCsv)?;
+
+    let statuses = vec![EliteStatus::Staff, EliteStatus::Veteran, EliteStatus::Elite, EliteStatus::Trial, EliteStatus::None];
     let elites = elite.elites_all(&statuses).await?;
 
-    Ok(Json(elites))
+    let mut report = ImportReport::plan(rows, &elites);
+    report.dry_run = dry_run;
+
+    // New elites have to be in the elite guild like with `POST /elites` and are tracked right away, so their current ign is needed
+    for mut create in std::mem::take(&mut report.creates) {
+        let Some(uuid) = create.elite.minecraft_uuid else {
+            continue;
+        };
+        if discord_api.get_elite_guild_member(&create.elite.discord_user_id).await?.is_none() {
+            report.conflicts.push(ImportConflict::new(
+                create.line,
+                format!("Discord user {} is not in the elite guild.", create.elite.discord_user_id),
+            ));
+            continue;
+        }
+        match mojang.get_profile(&uuid).await? {
+            Some(profile) => {
+                create.elite.ign = Some(profile.name);
+                report.creates.push(create);
+            }
+            None => report.conflicts.push(ImportConflict::new(create.line, format!("No player with uuid {uuid} exists."))),
+        }
+    }
+    report.conflicts.sort_by_key(|conflict| conflict.line);
+
+    if dry_run {
+        return Ok((StatusCode::OK, Json(report)));
+    }
+    if !report.conflicts.is_empty() {
+        return Ok((StatusCode::CONFLICT, Json(report)));
+    }
+
+    elite.import_elites(&report, &session.user.id).await?;
+    report.applied = true;
+
+    Ok((StatusCode::OK, Json(report)))
 }
 
-async fn patch_elite(
+#[derive(Debug, Deserialize)]
+struct BirthdaysQueryParams {
+    days: Option<i64>,
+}
+
+/// Returns the birthdays of active elites within the next `days` days (including today), soonest first
+async fn upcoming_birthdays(
+    State(elite): State<EliteService>,
+    Query(params): Query<BirthdaysQueryParams>,
+) -> Result<Json<Vec<UpcomingBirthday>>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /elites/birthdays/upcoming");
+
+    let days = params.days.unwrap_or(DEFAULT_BIRTHDAY_DAYS);
+    if !(0..=MAX_BIRTHDAY_DAYS).contains(&days) {
+        return Err(Error::InvalidBirthdayRange(days).into());
+    }
+
+    let today = Utc::now().date_naive();
+
+    let mut birthdays: Vec<UpcomingBirthday> = elite
+        .birthdays_all()
+        .await?
+        .iter()
+        .filter_map(|elite| UpcomingBirthday::for_elite(elite, today))
+        .filter(|birthday| birthday.days_until <= days)
+        .collect();
+    birthdays.sort_by_key(|birthday| (birthday.days_until, birthday.elite_id));
+
+    Ok(Json(birthdays))
+}
+
+async fn elite_changes(
     Path(elite_id): Path<i32>,
     State(elite): State<EliteService>,
-    Json(updated_elite): Json<EliteForUpdate>,
-) -> Result<Json<Option<Elite>>, AppError> {
-    debug!("{:<12} - {}{}", "HANDLER", "PATCH /elites/", elite_id);
-    debug!("{:?}", updated_elite);
+    Query(params): Query<OffsetQueryParams>,
+) -> Result<Json<Vec<EliteChange>>, AppError> {
+    debug!("{:<12} - {}{}{}", "HANDLER", "GET /elites/", elite_id, "/changes");
+
+    let limit = params.limit.unwrap_or(50).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
 
-    let updated_elite = elite.update_elite(elite_id, &updated_elite).await?;
+    let changes = elite.changes_for_elite(elite_id, limit, offset).await?;
 
-    Ok(Json(updated_elite))
+    Ok(Json(changes))
 }
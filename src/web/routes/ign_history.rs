@@ -1,17 +1,41 @@
+use crate::app::constants::NEXT_CURSOR_HEADER;
 use crate::app::error::AppError;
 use crate::app::state::AppState;
-use crate::model::recent_change::RecentChange;
+use crate::model::api_key::ApiKeyScope;
+use crate::model::mojang::is_valid_ign;
+use crate::model::name_history::PlayerNameHistory;
+use crate::model::permission::Permission;
+use crate::model::recent_change::{RECENT_CHANGE_EVENT_TYPES, RecentChangesCursor, RecentChangesFilter};
 use crate::service::IgnTrackerService;
-use crate::web::middleware::mw_staff_only::mw_staff_only;
-use axum::extract::{Query, State};
+use crate::web::error::Error;
+use crate::web::middleware::mw_api_key_scope::mw_api_key_scope;
+use crate::web::middleware::mw_permission::mw_permission;
+use axum::extract::{Path, Query, State};
+use axum::http::HeaderValue;
+use axum::response::{IntoResponse, Response};
 use axum::routing::get;
 use axum::{Json, Router, middleware};
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use tracing::debug;
+use uuid::Uuid;
+
+const MAX_PAGE_LIMIT: i64 = 100;
 
 pub fn routes(state: AppState) -> Router {
     Router::new()
-        .route("/ign-history/latest", get(history_latest).layer(middleware::from_fn(mw_staff_only)))
+        .route(
+            "/ign-history/latest",
+            get(history_latest)
+                .layer(middleware::from_fn_with_state(Permission::TrackerManage, mw_permission))
+                .layer(middleware::from_fn_with_state(ApiKeyScope::TrackerRead, mw_api_key_scope)),
+        )
+        .route(
+            "/ign-history/{player}",
+            get(history_player)
+                .layer(middleware::from_fn_with_state(Permission::TrackerManage, mw_permission))
+                .layer(middleware::from_fn_with_state(ApiKeyScope::TrackerRead, mw_api_key_scope)),
+        )
         .with_state(state)
 }
 
@@ -19,18 +43,79 @@ pub fn routes(state: AppState) -> Router {
 pub struct PageQueryParams {
     limit: Option<i64>,
     offset: Option<i64>,
+    cursor: Option<String>,
+    /// Comma separated list of event types
+    event_type: Option<String>,
+    uuid: Option<Uuid>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
 }
 
-async fn history_latest(
-    State(ign_tracker): State<IgnTrackerService>,
-    Query(params): Query<PageQueryParams>,
-) -> Result<Json<Vec<RecentChange>>, AppError> {
+/// Returns the latest changes as an array, newest first.
+/// If there are older changes, the cursor to request them with is returned in the `X-Next-Cursor` header.
+async fn history_latest(State(ign_tracker): State<IgnTrackerService>, Query(params): Query<PageQueryParams>) -> Result<Response, AppError> {
     debug!("{:<12} - {}", "HANDLER", "history_latest");
 
-    let limit = params.limit.unwrap_or(10);
-    let offset = params.offset.unwrap_or(0);
+    let limit = params.limit.unwrap_or(10).clamp(1, MAX_PAGE_LIMIT);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let cursor = match params.cursor {
+        Some(cursor) => Some(RecentChangesCursor::decode(&cursor).ok_or(Error::InvalidCursor)?),
+        None => None,
+    };
+
+    let event_types = match params.event_type {
+        Some(event_types) => {
+            let event_types: Vec<String> = event_types.split(',').map(|event_type| event_type.trim().to_string()).collect();
+            if let Some(unknown) = event_types.iter().find(|event_type| !RECENT_CHANGE_EVENT_TYPES.contains(&event_type.as_str())) {
+                return Err(Error::InvalidEventType(unknown.clone()).into());
+            }
+            Some(event_types)
+        }
+        None => None,
+    };
+
+    let filter = RecentChangesFilter {
+        event_types,
+        uuid: params.uuid,
+        since: params.since,
+        until: params.until,
+        cursor,
+    };
+
+    // Fetch one additional change to know whether there is a next page
+    let mut changes = ign_tracker.get_latest_changes(&filter, limit + 1, offset).await?;
+
+    let next_cursor = if changes.len() as i64 > limit {
+        changes.truncate(limit as usize);
+        changes.last().map(|change| RecentChangesCursor::after(change).encode())
+    } else {
+        None
+    };
+
+    match next_cursor {
+        Some(next_cursor) => {
+            let next_cursor = HeaderValue::from_str(&next_cursor).map_err(|_| AppError::InternalServerError)?;
+            Ok(([(NEXT_CURSOR_HEADER, next_cursor)], Json(changes)).into_response())
+        }
+        None => Ok(Json(changes).into_response()),
+    }
+}
+
+/// Returns the name history of a player by uuid, or of every player that currently uses or once used an ign.
+/// An ign lookup can return several candidates, the most likely owner comes first.
+async fn history_player(State(ign_tracker): State<IgnTrackerService>, Path(player): Path<String>) -> Result<Json<Vec<PlayerNameHistory>>, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "GET /ign-history/", player);
+
+    let name_histories = match Uuid::parse_str(&player) {
+        Ok(uuid) => ign_tracker.get_name_history(&uuid).await?.into_iter().collect(),
+        Err(_) if is_valid_ign(&player) => ign_tracker.find_name_histories_by_ign(&player).await?,
+        Err(_) => return Err(Error::InvalidIgn(player).into()),
+    };
 
-    let latest_changes = ign_tracker.get_latest_changes(limit, offset).await?;
+    if name_histories.is_empty() {
+        return Err(Error::PlayerNotFound(format!("No name history recorded for {player}.")).into());
+    }
 
-    Ok(Json(latest_changes))
+    Ok(Json(name_histories))
 }
@@ -0,0 +1,99 @@
+use crate::app::constants::SESSION_COOKIE_NAME;
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::impersonation::{DEFAULT_IMPERSONATION_MINUTES, ImpersonationAction, ImpersonationForCreate};
+use crate::model::permission::Permission;
+use crate::model::session::{Session, SessionUser};
+use crate::service::{ImpersonationService, SessionService};
+use crate::web::error::Error;
+use crate::web::middleware::mw_permission::mw_permission;
+use axum::extract::{Json, State};
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Router, middleware};
+use chrono::{Duration, Utc};
+use serde_json::{Value, json};
+use tower_cookies::Cookies;
+use tracing::{debug, info};
+
+/// Path of the impersonation endpoints, stopping is the only change allowed while impersonating
+pub const IMPERSONATION_PATH: &str = "/auth/impersonation";
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route(
+            IMPERSONATION_PATH,
+            post(start_impersonation)
+                .layer(middleware::from_fn_with_state(Permission::UsersImpersonate, mw_permission))
+                .delete(stop_impersonation),
+        )
+        .with_state(state)
+}
+
+/// Lets staff view the dashboard as another user for a limited time. Requests made meanwhile are read only and audited.
+async fn start_impersonation(
+    session: Session,
+    cookies: Cookies,
+    State(state): State<AppState>,
+    Json(mut new_impersonation): Json<ImpersonationForCreate>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    debug!("{:<12} - {}", "HANDLER", "POST /auth/impersonation");
+
+    new_impersonation.validate().map_err(AppError::InvalidFields)?;
+
+    let target_id = new_impersonation.discord_user_id;
+    if target_id == session.user.id {
+        return Err(Error::InvalidImpersonationTarget("You can not view as yourself.".to_string()).into());
+    }
+
+    let session_id = cookies.get(SESSION_COOKIE_NAME).ok_or(Error::SessionCookieNotFound)?.value().to_string();
+
+    let member = state
+        .discord
+        .api
+        .get_elite_guild_member(&target_id)
+        .await?
+        .ok_or_else(|| Error::DiscordUserNotInEliteGuild(target_id.clone()))?;
+    let role = state
+        .discord
+        .auth
+        .get_role_for_member(&member.roles)
+        .ok_or_else(|| Error::InvalidImpersonationTarget(format!("Discord user {target_id} is not an elite.")))?;
+    let mut permissions = state.roles.permissions_for(&role, &member.roles).await?;
+    // Viewing as someone never grants more than the staff member already has
+    permissions.retain(|permission| session.user.has_permission(*permission));
+
+    let target = SessionUser {
+        id: target_id,
+        role,
+        permissions,
+    };
+    let expires_at = Utc::now() + Duration::minutes(new_impersonation.minutes.unwrap_or(DEFAULT_IMPERSONATION_MINUTES));
+
+    state.session.start_impersonation(&session_id, &target, expires_at).await?;
+    state.impersonation.record(&session.user.id, &target.id, ImpersonationAction::Start, None, None).await?;
+
+    info!("{:<12} - {} is viewing as {} until {}", "HANDLER", session.user.id, target.id, expires_at);
+
+    Ok((StatusCode::CREATED, Json(json!({ "user": target, "expires_at": expires_at }))))
+}
+
+async fn stop_impersonation(
+    session: Session,
+    cookies: Cookies,
+    State(session_store): State<SessionService>,
+    State(impersonation): State<ImpersonationService>,
+) -> Result<StatusCode, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "DELETE /auth/impersonation");
+
+    let Some(impersonator) = &session.impersonator else {
+        return Ok(StatusCode::NO_CONTENT);
+    };
+
+    let session_id = cookies.get(SESSION_COOKIE_NAME).ok_or(Error::SessionCookieNotFound)?.value().to_string();
+
+    session_store.stop_impersonation(&session_id).await?;
+    impersonation.record(&impersonator.id, &session.user.id, ImpersonationAction::Stop, None, None).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
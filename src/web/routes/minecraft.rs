@@ -0,0 +1,109 @@
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::elite::EliteStatus;
+use crate::model::minecraft::{GroupAssignment, MinecraftServer, MinecraftServerForCreate, OpsEntry, WhitelistEntry};
+use crate::model::permission::Permission;
+use crate::service::{EliteService, MinecraftService};
+use crate::web::error::Error;
+use crate::web::middleware::mw_permission::mw_permission;
+use crate::web::middleware::mw_server_token::mw_server_token;
+use axum::extract::{Extension, Json, Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get};
+use axum::{Router, middleware};
+use serde_json::{Value, json};
+use tracing::debug;
+
+const MAX_SERVER_NAME_LENGTH: usize = 64;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route(
+            "/minecraft/servers",
+            get(servers).post(create_server).layer(middleware::from_fn_with_state(Permission::MinecraftManage, mw_permission)),
+        )
+        .route(
+            "/minecraft/servers/{server_id}",
+            delete(remove_server).layer(middleware::from_fn_with_state(Permission::MinecraftManage, mw_permission)),
+        )
+        .with_state(state)
+}
+
+/// Exports pulled by the game servers, authenticated by a per-server api token instead of a session
+pub fn server_routes(state: AppState) -> Router {
+    Router::new()
+        .route("/minecraft/whitelist.json", get(whitelist))
+        .route("/minecraft/ops.json", get(ops))
+        .route("/minecraft/luckperms.json", get(luckperms))
+        .layer(middleware::from_fn_with_state(state.clone(), mw_server_token))
+        .with_state(state)
+}
+
+async fn servers(State(minecraft): State<MinecraftService>) -> Result<Json<Vec<MinecraftServer>>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /minecraft/servers");
+
+    let servers = minecraft.servers_all().await?;
+
+    Ok(Json(servers))
+}
+
+/// Registers a game server, the response contains its api token which is not shown again
+async fn create_server(
+    State(minecraft): State<MinecraftService>,
+    Json(new_server): Json<MinecraftServerForCreate>,
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    debug!("{:<12} - {}", "HANDLER", "POST /minecraft/servers");
+
+    let name = new_server.name.trim();
+    if name.is_empty() || name.len() > MAX_SERVER_NAME_LENGTH {
+        return Err(Error::InvalidMinecraftServerName.into());
+    }
+
+    let (server, token) = minecraft.create_server(name).await?;
+
+    Ok((StatusCode::CREATED, Json(json!({ "server": server, "token": token }))))
+}
+
+async fn remove_server(State(minecraft): State<MinecraftService>, Path(server_id): Path<i32>) -> Result<StatusCode, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "DELETE /minecraft/servers/", server_id);
+
+    minecraft.remove_server(server_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn whitelist(
+    Extension(server): Extension<MinecraftServer>,
+    State(minecraft): State<MinecraftService>,
+    State(elite): State<EliteService>,
+) -> Result<Json<Vec<WhitelistEntry>>, AppError> {
+    debug!("{:<12} - {} | server={}", "HANDLER", "GET /minecraft/whitelist.json", server.name);
+
+    let elites = elite.elites_all(&vec![EliteStatus::Staff, EliteStatus::Veteran, EliteStatus::Elite, EliteStatus::Trial]).await?;
+
+    Ok(Json(minecraft.whitelist(&elites)))
+}
+
+async fn ops(
+    Extension(server): Extension<MinecraftServer>,
+    State(minecraft): State<MinecraftService>,
+    State(elite): State<EliteService>,
+) -> Result<Json<Vec<OpsEntry>>, AppError> {
+    debug!("{:<12} - {} | server={}", "HANDLER", "GET /minecraft/ops.json", server.name);
+
+    let elites = elite.elites_all(&vec![EliteStatus::Staff]).await?;
+
+    Ok(Json(minecraft.ops(&elites)))
+}
+
+async fn luckperms(
+    Extension(server): Extension<MinecraftServer>,
+    State(minecraft): State<MinecraftService>,
+    State(elite): State<EliteService>,
+) -> Result<Json<Vec<GroupAssignment>>, AppError> {
+    debug!("{:<12} - {} | server={}", "HANDLER", "GET /minecraft/luckperms.json", server.name);
+
+    let elites = elite.elites_all(&vec![EliteStatus::Staff, EliteStatus::Veteran, EliteStatus::Elite, EliteStatus::Trial]).await?;
+
+    Ok(Json(minecraft.group_assignments(&elites)))
+}
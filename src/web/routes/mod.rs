@@ -1,5 +1,24 @@
+pub mod api_keys;
+pub mod audit;
 pub mod auth;
+pub mod avatar;
+pub mod calendar;
 pub mod dashboard;
 pub mod discord;
 pub mod elite;
 pub mod ign_history;
+pub mod impersonation;
+pub mod minecraft;
+pub mod roles;
+pub mod sessions;
+pub mod skin_history;
+pub mod tracked_uuids;
+
+use serde::Deserialize;
+
+/// Offset pagination shared by the list endpoints that don't use cursors
+#[derive(Debug, Deserialize)]
+pub struct OffsetQueryParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
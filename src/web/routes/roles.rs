@@ -0,0 +1,60 @@
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::permission::{Permission, RoleMapping, RoleMappingForUpdate};
+use crate::model::session::Session;
+use crate::service::RoleService;
+use crate::web::middleware::mw_permission::mw_permission;
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, put};
+use axum::{Router, middleware};
+use strum::IntoEnumIterator;
+use tracing::debug;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route("/roles", get(role_mappings))
+        .route("/roles/permissions", get(permissions))
+        .route("/roles/{discord_role_id}", put(save_role_mapping).delete(remove_role_mapping))
+        .layer(middleware::from_fn_with_state(Permission::RolesManage, mw_permission))
+        .with_state(state)
+}
+
+async fn role_mappings(State(roles): State<RoleService>) -> Result<Json<Vec<RoleMapping>>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /roles");
+
+    let mappings = roles.mappings_all().await?;
+
+    Ok(Json(mappings))
+}
+
+/// Every permission a role can be given
+async fn permissions() -> Json<Vec<Permission>> {
+    debug!("{:<12} - {}", "HANDLER", "GET /roles/permissions");
+
+    Json(Permission::iter().collect())
+}
+
+/// Sets the permissions of a discord role. Sessions pick up the change on their next role check.
+async fn save_role_mapping(
+    session: Session,
+    State(roles): State<RoleService>,
+    Path(discord_role_id): Path<String>,
+    Json(mut mapping): Json<RoleMappingForUpdate>,
+) -> Result<Json<RoleMapping>, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "PUT /roles/", discord_role_id);
+
+    mapping.validate().map_err(AppError::InvalidFields)?;
+
+    let mapping = roles.save_mapping(&discord_role_id, &mapping, &session.user.id).await?;
+
+    Ok(Json(mapping))
+}
+
+async fn remove_role_mapping(State(roles): State<RoleService>, Path(discord_role_id): Path<String>) -> Result<StatusCode, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "DELETE /roles/", discord_role_id);
+
+    roles.remove_mapping(&discord_role_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,90 @@
+use crate::app::constants::SESSION_COOKIE_NAME;
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::auth_event::{AuthEventForCreate, AuthEventKind, RequestOrigin};
+use crate::model::permission::Permission;
+use crate::model::session::{Session, SessionInfo};
+use crate::service::{AuthEventService, DiscordAuthService, SessionService};
+use crate::web::middleware::mw_permission::mw_permission;
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get};
+use axum::{Router, middleware};
+use serde_json::{Value, json};
+use tower_cookies::Cookies;
+use tracing::debug;
+
+/// Sessions of the current user, not available while viewing as another user
+pub const SESSIONS_PATH: &str = "/auth/sessions";
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route(SESSIONS_PATH, get(sessions))
+        .route("/auth/sessions/{session_id}", delete(revoke_session))
+        .route(
+            "/users/{discord_id}/sessions",
+            delete(revoke_user_sessions).layer(middleware::from_fn_with_state(Permission::SessionsManage, mw_permission)),
+        )
+        .with_state(state)
+}
+
+/// Devices the current user is logged in on
+async fn sessions(session: Session, State(session_store): State<SessionService>, cookies: Cookies) -> Result<Json<Vec<SessionInfo>>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /auth/sessions");
+
+    let current_session_id = cookies.get(SESSION_COOKIE_NAME).map(|cookie| cookie.value().to_string());
+
+    let sessions = session_store.user_sessions(&session.user.id, current_session_id.as_deref()).await?;
+
+    Ok(Json(sessions))
+}
+
+/// Logs the current user out on one of their devices
+async fn revoke_session(
+    session: Session,
+    State(session_store): State<SessionService>,
+    State(discord_auth): State<DiscordAuthService>,
+    State(auth_events): State<AuthEventService>,
+    origin: RequestOrigin,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "DELETE /auth/sessions/", session_id);
+
+    let tokens = session_store.invalidate_user_session(&session.user.id, &session_id).await?;
+    discord_auth.revoke_tokens(tokens.into_iter().collect());
+
+    let event = AuthEventForCreate {
+        details: Some(json!({ "session_id": session_id })),
+        ..AuthEventForCreate::new(AuthEventKind::SessionRevoked, Some(&session.user.id))
+    };
+    auth_events.record(event, &origin).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Logs a user out on every device, e.g. when their account is compromised
+async fn revoke_user_sessions(
+    session: Session,
+    State(session_store): State<SessionService>,
+    State(discord_auth): State<DiscordAuthService>,
+    State(auth_events): State<AuthEventService>,
+    origin: RequestOrigin,
+    Path(discord_id): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    debug!("{:<12} - {}{}{}", "HANDLER", "DELETE /users/", discord_id, "/sessions");
+
+    let tokens = session_store.invalidate_user_sessions(&discord_id).await?;
+    let revoked = tokens.len();
+    discord_auth.revoke_tokens(tokens);
+
+    debug!("{:<12} - {} revoked {} sessions of {}", "HANDLER", session.user.id, revoked, discord_id);
+
+    let event = AuthEventForCreate {
+        actor_id: Some(session.user.id),
+        details: Some(json!({ "revoked": revoked })),
+        ..AuthEventForCreate::new(AuthEventKind::SessionRevoked, Some(&discord_id))
+    };
+    auth_events.record(event, &origin).await;
+
+    Ok(Json(json!({ "revoked": revoked })))
+}
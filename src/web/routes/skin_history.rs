@@ -0,0 +1,32 @@
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::api_key::ApiKeyScope;
+use crate::model::permission::Permission;
+use crate::model::skin_history::SkinHistoryEntry;
+use crate::service::IgnTrackerService;
+use crate::web::middleware::mw_api_key_scope::mw_api_key_scope;
+use crate::web::middleware::mw_permission::mw_permission;
+use axum::extract::{Path, State};
+use axum::routing::get;
+use axum::{Json, Router, middleware};
+use tracing::debug;
+use uuid::Uuid;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route(
+            "/skin-history/{uuid}",
+            get(skin_history)
+                .layer(middleware::from_fn_with_state(Permission::TrackerManage, mw_permission))
+                .layer(middleware::from_fn_with_state(ApiKeyScope::TrackerRead, mw_api_key_scope)),
+        )
+        .with_state(state)
+}
+
+async fn skin_history(State(ign_tracker): State<IgnTrackerService>, Path(uuid): Path<Uuid>) -> Result<Json<Vec<SkinHistoryEntry>>, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "GET /skin-history/", uuid);
+
+    let skin_history = ign_tracker.get_skin_history(&uuid).await?;
+
+    Ok(Json(skin_history))
+}
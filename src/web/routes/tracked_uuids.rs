@@ -0,0 +1,81 @@
+use crate::app::error::AppError;
+use crate::app::state::AppState;
+use crate::model::api_key::ApiKeyScope;
+use crate::model::mojang::is_valid_ign;
+use crate::model::permission::Permission;
+use crate::model::tracked_uuid::{TrackedUuid, TrackedUuidForCreate};
+use crate::service::{IgnTrackerService, MojangApiService};
+use crate::web::error::Error;
+use crate::web::middleware::mw_api_key_scope::mw_api_key_scope;
+use crate::web::middleware::mw_permission::mw_permission;
+use axum::extract::{Json, Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post};
+use axum::{Router, middleware};
+use tracing::debug;
+use uuid::Uuid;
+
+pub fn routes(state: AppState) -> Router {
+    Router::new()
+        .route(
+            "/tracked-uuids",
+            get(tracked_uuids)
+                .layer(middleware::from_fn_with_state(Permission::TrackerManage, mw_permission))
+                .layer(middleware::from_fn_with_state(ApiKeyScope::TrackerRead, mw_api_key_scope))
+                .merge(
+                    post(add_tracked_uuid)
+                        .layer(middleware::from_fn_with_state(Permission::TrackerManage, mw_permission))
+                        .layer(middleware::from_fn_with_state(ApiKeyScope::TrackerWrite, mw_api_key_scope)),
+                ),
+        )
+        .route(
+            "/tracked-uuids/{uuid}",
+            delete(remove_tracked_uuid)
+                .layer(middleware::from_fn_with_state(Permission::TrackerManage, mw_permission))
+                .layer(middleware::from_fn_with_state(ApiKeyScope::TrackerWrite, mw_api_key_scope)),
+        )
+        .with_state(state)
+}
+
+async fn tracked_uuids(State(ign_tracker): State<IgnTrackerService>) -> Result<Json<Vec<TrackedUuid>>, AppError> {
+    debug!("{:<12} - {}", "HANDLER", "GET /tracked-uuids");
+
+    let tracked_uuids = ign_tracker.tracked_uuids_all().await?;
+
+    Ok(Json(tracked_uuids))
+}
+
+async fn add_tracked_uuid(
+    State(ign_tracker): State<IgnTrackerService>,
+    State(mojang): State<MojangApiService>,
+    Json(tracked_uuid): Json<TrackedUuidForCreate>,
+) -> Result<(StatusCode, Json<TrackedUuid>), AppError> {
+    debug!("{:<12} - {}", "HANDLER", "POST /tracked-uuids");
+    debug!("{:?}", tracked_uuid);
+
+    let profile = match (tracked_uuid.uuid, tracked_uuid.ign) {
+        (Some(uuid), None) => mojang.get_profile(&uuid).await?.ok_or_else(|| Error::PlayerNotFound(format!("No player with uuid {uuid} exists.")))?,
+        (None, Some(ign)) => {
+            if !is_valid_ign(&ign) {
+                return Err(Error::InvalidIgn(ign).into());
+            }
+            mojang
+                .get_profile_by_name(&ign)
+                .await?
+                .ok_or_else(|| Error::PlayerNotFound(format!("No player with ign {ign} exists.")))?
+        }
+        _ => return Err(Error::InvalidTrackedUuidForCreate.into()),
+    };
+
+    let tracked_uuid = ign_tracker.add_tracked_uuid(&profile.id, &profile.name).await?;
+
+    Ok((StatusCode::CREATED, Json(tracked_uuid)))
+}
+
+async fn remove_tracked_uuid(State(ign_tracker): State<IgnTrackerService>, Path(uuid): Path<Uuid>) -> Result<StatusCode, AppError> {
+    debug!("{:<12} - {}{}", "HANDLER", "DELETE /tracked-uuids/", uuid);
+
+    ign_tracker.remove_tracked_uuid(&uuid).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}